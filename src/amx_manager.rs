@@ -2,74 +2,72 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use parking_lot::RwLock;
 use samp::prelude::*;
+use samp::amx::AmxIdent;
 use crate::error::{TimerError, TimerResult};
 use crate::callback::{CallbackData, CallbackParam};
 
-/* thread-safe wrapper for AMX pointer */
-struct AmxWrapper(*mut Amx);
-unsafe impl Send for AmxWrapper {}
-unsafe impl Sync for AmxWrapper {}
-
-/* global AMX instance storage */
+/* global AMX instance storage, keyed by the raw AMX pointer */
 lazy_static::lazy_static! {
-    static ref AMX_INSTANCES: Arc<RwLock<HashMap<usize, AmxWrapper>>> = Arc::new(RwLock::new(HashMap::new()));
+    static ref AMX_INSTANCES: Arc<RwLock<HashMap<usize, AmxIdent>>> = Arc::new(RwLock::new(HashMap::new()));
 }
 
 pub struct AmxManager;
 
 impl AmxManager {
-    pub fn register_amx(amx: &Amx) {
-        let amx_ptr = amx as *const Amx as *mut Amx;
-        let amx_id = amx_ptr as usize;
+    /* the id timers use to refer back to an instance */
+    pub fn amx_id(amx: &Amx) -> usize {
+        amx.amx().as_ptr() as usize
+    }
 
-        if amx_ptr.is_null() {
-            return;
-        }
+    pub fn register_amx(amx: &Amx) -> usize {
+        let ident = amx.ident();
+        let amx_id = Self::amx_id(amx);
 
         {
             let instances = AMX_INSTANCES.read();
             if instances.contains_key(&amx_id) {
-                return; 
+                return amx_id;
             }
         }
 
         let mut instances = AMX_INSTANCES.write();
 
-        if !instances.contains_key(&amx_id) {
-            instances.insert(amx_id, AmxWrapper(amx_ptr));
-            tracing::debug!("Registered new AMX instance: {:p}", amx_ptr);
+        if let std::collections::hash_map::Entry::Vacant(entry) = instances.entry(amx_id) {
+            entry.insert(ident);
+            tracing::debug!("Registered new AMX instance: {:#x}", amx_id);
         }
+
+        amx_id
     }
-    
-    pub fn execute_callback(callback_name: &str, params: &Option<CallbackData>) -> TimerResult<i32> {
+
+    pub fn unregister_amx(amx: &Amx) -> usize {
+        let amx_id = Self::amx_id(amx);
+
+        if AMX_INSTANCES.write().remove(&amx_id).is_some() {
+            tracing::debug!("Unregistered AMX instance: {:#x}", amx_id);
+        }
+
+        amx_id
+    }
+
+    pub fn execute_callback(amx_id: usize, callback_name: &str, params: &Option<CallbackData>) -> TimerResult<i32> {
         if callback_name.is_empty() {
             return Err(TimerError::InvalidCallback("Empty callback name".to_string()));
         }
 
-        let amx_ptr = {
+        let ident = {
             let instances = AMX_INSTANCES.read();
-            if instances.is_empty() {
-                return Err(TimerError::Internal("No AMX instances available".to_string()));
-            }
-
-            let amx_wrapper = instances.values().next()
-                .ok_or_else(|| TimerError::Internal("Failed to get AMX instance".to_string()))?;
-
-            if amx_wrapper.0.is_null() {
-                return Err(TimerError::Internal("AMX instance pointer is null".to_string()));
-            }
-
-            amx_wrapper.0
-        }; 
+            *instances.get(&amx_id)
+                .ok_or_else(|| TimerError::Internal(format!("AMX instance {:#x} is not registered", amx_id)))?
+        };
 
-        unsafe {
-            let amx_ref = amx_ptr.as_ref()
-                .ok_or_else(|| TimerError::Internal("AMX instance reference is invalid".to_string()))?;
+        /* resolved on every call: the runtime owns the Amx and may move it */
+        let amx = samp::amx::get(ident)
+            .ok_or_else(|| TimerError::Internal(format!("AMX instance {:#x} is no longer loaded", amx_id)))?;
 
-            Self::execute_callback_on_amx(amx_ref, callback_name, params)
-        }
+        unsafe { Self::execute_callback_on_amx(amx, callback_name, params) }
     }
-    
+
     unsafe fn execute_callback_on_amx(amx: &Amx, callback_name: &str, params: &Option<CallbackData>) -> TimerResult<i32> {
         if callback_name.is_empty() || callback_name.len() > 64 {
             return Err(TimerError::InvalidCallback(
@@ -86,12 +84,12 @@ impl AmxManager {
             }
         };
 
+        /* one allocator for all parameters: its heap is released on drop, so it must outlive exec */
+        let allocator = amx.allocator();
+
         if let Some(callback_data) = params {
-            if callback_data.params.len() > 16 {
-                return Err(TimerError::ParameterValidation(
-                    "Too many parameters".to_string()
-                ));
-            }
+            /* same limits the timer was created with, see limits in timers.toml */
+            callback_data.validate()?;
 
             for param in callback_data.params.iter().rev() {
                 match param {
@@ -103,9 +101,6 @@ impl AmxManager {
                         }
                     }
                     CallbackParam::Float(val) => {
-                        if !val.is_finite() {
-                            return Err(TimerError::ParameterValidation("Invalid float".to_string()));
-                        }
                         if let Err(e) = amx.push(*val) {
                             return Err(TimerError::CallbackExecutionError(
                                 format!("Failed to push float: {:?}", e)
@@ -113,11 +108,6 @@ impl AmxManager {
                         }
                     }
                     CallbackParam::String(val) => {
-                        if val.len() > 1024 {
-                            return Err(TimerError::ParameterValidation("String too long".to_string()));
-                        }
-
-                        let allocator = amx.allocator();
                         match allocator.allot_string(val) {
                             Ok(amx_string) => {
                                 if let Err(e) = amx.push(amx_string) {
@@ -133,6 +123,22 @@ impl AmxManager {
                             }
                         }
                     }
+                    CallbackParam::Array(val) => {
+                        match allocator.allot_array(val) {
+                            Ok(amx_buffer) => {
+                                if let Err(e) = amx.push(amx_buffer) {
+                                    return Err(TimerError::CallbackExecutionError(
+                                        format!("Failed to push array: {:?}", e)
+                                    ));
+                                }
+                            }
+                            Err(e) => {
+                                return Err(TimerError::CallbackExecutionError(
+                                    format!("Failed to allocate array: {:?}", e)
+                                ));
+                            }
+                        }
+                    }
                 }
             }
         }
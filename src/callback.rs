@@ -1,23 +1,40 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
 use crate::error::{TimerError, TimerResult};
 use crate::amx_manager::AmxManager;
+use crate::config::{self, LimitsConfig};
+#[cfg(feature = "metrics")]
+use crate::metrics;
 
-const MAX_CALLBACK_PARAMS: usize = 16;
-const MAX_STRING_PARAM_LENGTH: usize = 1024;
+pub const MAX_ARRAY_PARAM_LENGTH: usize = 1024;
 
-#[derive(Debug, Clone)]
+/* serialized as {"type": "integer", "value": 5} */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
 pub enum CallbackParam {
     Integer(i32),
     Float(f32),
     String(String),
+    Array(Vec<i32>),
 }
 
 impl CallbackParam {
     pub fn validate(&self) -> TimerResult<()> {
+        self.validate_with(&config::get().limits)
+    }
+
+    pub fn validate_with(&self, limits: &LimitsConfig) -> TimerResult<()> {
         match self {
             CallbackParam::String(s) => {
-                if s.len() > MAX_STRING_PARAM_LENGTH {
+                let max_length = limits.max_string_param_length;
+                if s.len() > max_length {
                     return Err(TimerError::ParameterValidation(
-                        format!("String parameter too long: {} > {} chars", s.len(), MAX_STRING_PARAM_LENGTH)
+                        format!("String parameter too long: {} > {} chars", s.len(), max_length)
                     ));
                 }
             }
@@ -28,13 +45,67 @@ impl CallbackParam {
                     ));
                 }
             }
+            CallbackParam::Array(a) => {
+                if a.len() > MAX_ARRAY_PARAM_LENGTH {
+                    return Err(TimerError::ParameterValidation(
+                        format!("Array parameter too long: {} > {} cells", a.len(), MAX_ARRAY_PARAM_LENGTH)
+                    ));
+                }
+            }
             CallbackParam::Integer(_) => {} /* always valid */
         }
         Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
+/* SetTimerEx-style format specifiers, one per callback parameter */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatSpecifier {
+    Integer,
+    Float,
+    String,
+    Array, /* always followed by an Integer holding its length */
+}
+
+pub fn parse_format(format: &str) -> TimerResult<Vec<FormatSpecifier>> {
+    let mut specifiers = Vec::with_capacity(format.len());
+    let mut chars = format.chars().enumerate().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let specifier = match c {
+            'i' | 'd' => FormatSpecifier::Integer,
+            'f' => FormatSpecifier::Float,
+            's' => FormatSpecifier::String,
+            'a' => {
+                if !matches!(chars.peek(), Some((_, 'i' | 'd'))) {
+                    return Err(TimerError::ParameterParseError(
+                        format!("Array at position {} must be followed by its length ('i' or 'd')", position)
+                    ));
+                }
+                FormatSpecifier::Array
+            }
+            _ => {
+                return Err(TimerError::ParameterParseError(
+                    format!("Unknown format specifier '{}' at position {}", c, position)
+                ));
+            }
+        };
+
+        let max_params = config::get().limits.max_callback_params;
+        if specifiers.len() >= max_params {
+            return Err(TimerError::ParameterValidation(
+                format!("Too many parameters: {} > {}", format.len(), max_params)
+            ));
+        }
+
+        specifiers.push(specifier);
+    }
+
+    Ok(specifiers)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct CallbackData {
     pub params: Vec<CallbackParam>,
 }
@@ -51,15 +122,16 @@ impl CallbackData {
             Self::new()
         } else {
             CallbackData {
-                params: Vec::with_capacity(capacity.min(MAX_CALLBACK_PARAMS)),
+                params: Vec::with_capacity(capacity.min(config::get().limits.max_callback_params)),
             }
         }
     }
 
     pub fn add_param(&mut self, param: CallbackParam) -> TimerResult<()> {
-        if self.params.len() >= MAX_CALLBACK_PARAMS {
+        let max_params = config::get().limits.max_callback_params;
+        if self.params.len() >= max_params {
             return Err(TimerError::ParameterValidation(
-                format!("Too many parameters: {} > {}", self.params.len() + 1, MAX_CALLBACK_PARAMS)
+                format!("Too many parameters: {} > {}", self.params.len() + 1, max_params)
             ));
         }
 
@@ -69,17 +141,60 @@ impl CallbackData {
     }
 
     pub fn validate(&self) -> TimerResult<()> {
+        self.validate_with(&config::get().limits)
+    }
+
+    pub fn validate_with(&self, limits: &LimitsConfig) -> TimerResult<()> {
+        if self.params.len() > limits.max_callback_params {
+            return Err(TimerError::ParameterValidation(
+                format!("Too many parameters: {} > {}", self.params.len(), limits.max_callback_params)
+            ));
+        }
+
         for param in &self.params {
-            param.validate()?;
+            param.validate_with(limits)?;
         }
         Ok(())
     }
 }
 
+/* callback waiting to be executed on the server thread */
+pub struct PendingCallback {
+    pub timer_id: i32,
+    pub owner_amx: usize,
+    pub callback: String,
+    pub params: Option<CallbackData>,
+    pub enqueued_at: Instant,
+    reply: oneshot::Sender<TimerResult<Duration>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DispatchStats {
+    pub queue_depth: usize,
+    pub last_drain_latency: Duration,
+    pub max_drain_latency: Duration,
+}
+
+/* expired timers are queued here by the tokio workers and drained in process_tick */
+lazy_static::lazy_static! {
+    static ref PENDING_CALLBACKS: Mutex<VecDeque<PendingCallback>> = Mutex::new(VecDeque::new());
+}
+
+static QUEUE_DEPTH: AtomicUsize = AtomicUsize::new(0);
+static LAST_DRAIN_LATENCY_US: AtomicU64 = AtomicU64::new(0);
+static MAX_DRAIN_LATENCY_US: AtomicU64 = AtomicU64::new(0);
+
+/* tests draining the shared queue hold this, so they don't drop each other's callbacks */
+#[cfg(test)]
+pub(crate) static DISPATCH_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/* queues the callback for the server thread and waits until it has been executed, returning how long it ran */
 pub async fn execute_callback(
+    timer_id: i32,
+    owner_amx: usize,
     callback_name: &str,
     params: &Option<CallbackData>,
-) -> TimerResult<()> {
+) -> TimerResult<Duration> {
     if callback_name.trim().is_empty() {
         return Err(TimerError::InvalidCallback(callback_name.to_string()));
     }
@@ -88,11 +203,101 @@ pub async fn execute_callback(
         callback_data.validate()?;
     }
 
+    let (reply, response) = oneshot::channel();
+    {
+        let mut queue = PENDING_CALLBACKS.lock();
+        queue.push_back(PendingCallback {
+            timer_id,
+            owner_amx,
+            callback: callback_name.to_string(),
+            params: params.clone(),
+            enqueued_at: Instant::now(),
+            reply,
+        });
+        QUEUE_DEPTH.store(queue.len(), Ordering::Release);
+    }
+
+    tracing::trace!("Timer {} queued callback {} for dispatch", timer_id, callback_name);
+
+    match response.await {
+        Ok(result) => result,
+        Err(_) => Err(TimerError::Internal(
+            format!("Callback {} was dropped before dispatch", callback_name)
+        )),
+    }
+}
+
+/* must only be called from the server thread (process_tick) */
+pub fn process_pending_callbacks<F: Fn(i32) -> bool>(is_timer_active: F) -> usize {
+    let pending: Vec<PendingCallback> = {
+        let mut queue = PENDING_CALLBACKS.lock();
+        QUEUE_DEPTH.store(0, Ordering::Release);
+        queue.drain(..).collect()
+    };
+
+    if pending.is_empty() {
+        return 0;
+    }
+
+    let drain_start = Instant::now();
+    let mut worst_latency = Duration::ZERO;
+    let mut executed = 0;
+
+    for item in pending {
+        if item.reply.is_closed() || !is_timer_active(item.timer_id) {
+            tracing::trace!("Skipping queued callback {} for killed timer {}", item.callback, item.timer_id);
+            continue;
+        }
+
+        worst_latency = worst_latency.max(drain_start.saturating_duration_since(item.enqueued_at));
+
+        let result = {
+            let _span = tracing::error_span!("timer", timer_id = item.timer_id).entered();
+            run_callback(item.owner_amx, &item.callback, &item.params)
+        };
+        executed += 1;
+
+        /* receiver is gone if the timer was killed during execution */
+        let _ = item.reply.send(result);
+    }
+
+    let latency_us = worst_latency.as_micros() as u64;
+    LAST_DRAIN_LATENCY_US.store(latency_us, Ordering::Release);
+    MAX_DRAIN_LATENCY_US.fetch_max(latency_us, Ordering::AcqRel);
+
+    if worst_latency.as_millis() > 50 {
+        tracing::warn!("Callback dispatch lagging: {} callbacks waited up to {:?}", executed, worst_latency);
+    }
+
+    executed
+}
+
+pub fn clear_pending_callbacks() -> usize {
+    let mut queue = PENDING_CALLBACKS.lock();
+    let count = queue.len();
+    queue.clear();
+    QUEUE_DEPTH.store(0, Ordering::Release);
+    count
+}
+
+pub fn dispatch_stats() -> DispatchStats {
+    DispatchStats {
+        queue_depth: QUEUE_DEPTH.load(Ordering::Acquire),
+        last_drain_latency: Duration::from_micros(LAST_DRAIN_LATENCY_US.load(Ordering::Acquire)),
+        max_drain_latency: Duration::from_micros(MAX_DRAIN_LATENCY_US.load(Ordering::Acquire)),
+    }
+}
+
+fn run_callback(
+    owner_amx: usize,
+    callback_name: &str,
+    params: &Option<CallbackData>,
+) -> TimerResult<Duration> {
     tracing::debug!("Executing callback: {} with {} parameters",
                    callback_name,
                    params.as_ref().map_or(0, |p| p.params.len()));
 
-    let execution_start = std::time::Instant::now();
+    let execution_start = Instant::now();
 
     if !AmxManager::has_instances() {
         tracing::warn!("No AMX instances available, simulating callback execution for: {}", callback_name);
@@ -111,6 +316,9 @@ pub async fn execute_callback(
                         CallbackParam::String(val) => {
                             tracing::trace!("  [{}]: string = '{}' (len={})", i, val, val.len());
                         }
+                        CallbackParam::Array(val) => {
+                            tracing::trace!("  [{}]: array = {:?} (len={})", i, val, val.len());
+                        }
                     }
                 }
             }
@@ -119,14 +327,18 @@ pub async fn execute_callback(
             }
         }
     } else {
-        tracing::debug!("Executing actual SAMP callback: {}", callback_name);
+        tracing::debug!("Executing actual SAMP callback: {} on AMX {:#x}", callback_name, owner_amx);
 
-        match AmxManager::execute_callback(callback_name, params) {
+        match AmxManager::execute_callback(owner_amx, callback_name, params) {
             Ok(return_value) => {
                 tracing::debug!("Callback {} returned: {}", callback_name, return_value);
             }
             Err(e) => {
                 tracing::error!("Callback {} execution failed: {}", callback_name, e);
+
+                #[cfg(feature = "metrics")]
+                metrics::callback_executed(execution_start.elapsed());
+
                 return Err(e);
             }
         }
@@ -135,11 +347,15 @@ pub async fn execute_callback(
     let execution_time = execution_start.elapsed();
     tracing::debug!("Callback {} completed in {:?}", callback_name, execution_time);
 
-    if execution_time.as_millis() > 10 {
+    #[cfg(feature = "metrics")]
+    metrics::callback_executed(execution_time);
+
+    let slow_callback_ms = config::get().warnings.slow_callback_ms;
+    if slow_callback_ms > 0 && execution_time.as_millis() > slow_callback_ms as u128 {
         tracing::warn!("Slow callback execution: {} took {:?}", callback_name, execution_time);
     }
 
-    Ok(())
+    Ok(execution_time)
 }
 
 pub fn is_valid_callback_name(name: &str) -> bool {
@@ -185,6 +401,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_callback_data_creation() {
         let mut data = CallbackData::new();
         assert_eq!(data.params.len(), 0);
@@ -227,4 +444,82 @@ mod tests {
         assert!(!is_valid_callback_name("abc ")); //trailing space should be invalid
         assert!(!is_valid_callback_name(" abc ")); //both spaces should be invalid
     }
+
+    #[test]
+    fn test_raised_param_limits() {
+        let limits = LimitsConfig {
+            max_callback_params: 24,
+            max_string_param_length: 4096,
+            ..LimitsConfig::default()
+        };
+        let mut params: Vec<CallbackParam> = (0..23).map(CallbackParam::Integer).collect();
+        params.push(CallbackParam::String("x".repeat(2000)));
+        let mut data = CallbackData { params };
+
+        /* the check execute_callback_on_amx runs before pushing the parameters */
+        assert!(data.validate_with(&limits).is_ok());
+        assert!(matches!(data.validate_with(&LimitsConfig::default()), Err(TimerError::ParameterValidation(_))));
+
+        data.params.push(CallbackParam::Integer(24));
+        assert!(matches!(data.validate_with(&limits), Err(TimerError::ParameterValidation(_))));
+    }
+
+    #[test]
+    fn test_callbacks_dispatched_from_queue() {
+        let _dispatch = DISPATCH_TEST_LOCK.lock();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to build runtime");
+
+        let mut data = CallbackData::new();
+        data.add_param(CallbackParam::Integer(7)).expect("Failed to add integer param");
+
+        let handle = runtime.spawn(async move {
+            execute_callback(-100, 0, "QueuedCallback", &Some(data)).await
+        });
+
+        while dispatch_stats().queue_depth == 0 {
+            runtime.block_on(tokio::task::yield_now());
+        }
+
+        assert_eq!(process_pending_callbacks(|timer_id| timer_id == -100), 1);
+        assert_eq!(dispatch_stats().queue_depth, 0);
+
+        let result = runtime.block_on(handle).expect("Dispatch task panicked");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            parse_format("ifs").expect("Failed to parse format"),
+            vec![FormatSpecifier::Integer, FormatSpecifier::Float, FormatSpecifier::String]
+        );
+        assert_eq!(parse_format("d").expect("Failed to parse format"), vec![FormatSpecifier::Integer]);
+        assert!(parse_format("").expect("Failed to parse format").is_empty());
+
+        assert!(matches!(parse_format("ix"), Err(TimerError::ParameterParseError(_))));
+        let max_params = config::get().limits.max_callback_params;
+        assert!(matches!(parse_format(&"i".repeat(max_params + 1)), Err(TimerError::ParameterValidation(_))));
+        assert!(parse_format(&"i".repeat(max_params)).is_ok());
+
+        assert_eq!(
+            parse_format("aid").expect("Failed to parse format"),
+            vec![FormatSpecifier::Array, FormatSpecifier::Integer, FormatSpecifier::Integer]
+        );
+        assert!(matches!(parse_format("a"), Err(TimerError::ParameterParseError(_))));
+        assert!(matches!(parse_format("as"), Err(TimerError::ParameterParseError(_))));
+    }
+
+    #[test]
+    fn test_array_param_validation() {
+        let mut data = CallbackData::new();
+        data.add_param(CallbackParam::Array(vec![1, 2, 3])).expect("Failed to add array param");
+        data.add_param(CallbackParam::Array(Vec::new())).expect("Failed to add empty array param");
+
+        let oversized = CallbackParam::Array(vec![0; MAX_ARRAY_PARAM_LENGTH + 1]);
+        assert!(matches!(data.add_param(oversized), Err(TimerError::ParameterValidation(_))));
+        assert_eq!(data.params.len(), 2);
+    }
 }
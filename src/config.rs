@@ -0,0 +1,341 @@
+use std::path::Path;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::cron::CronTimezone;
+use crate::error::{TimerError, TimerResult};
+
+/* read from the server directory, next to server.cfg */
+pub const CONFIG_PATH: &str = "timers.toml";
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub runtime: RuntimeConfig,
+    pub limits: LimitsConfig,
+    pub warnings: WarningsConfig,
+    pub log: LogConfig,
+    pub server_log: ServerLogConfig,
+    pub metrics: MetricsConfig,
+    pub dump: DumpConfig,
+    pub persistence: PersistenceConfig,
+    pub cron: CronConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeConfig {
+    pub worker_threads: usize,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        RuntimeConfig {
+            worker_threads: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub max_timers: usize,
+    /* quotas so one script can't use up max_timers, 0 means no quota */
+    pub max_timers_per_script: usize,
+    pub max_timers_per_group: usize,
+    pub max_callback_params: usize,
+    pub max_string_param_length: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            max_timers: 10000,
+            max_timers_per_script: 0,
+            max_timers_per_group: 0,
+            max_callback_params: 16,
+            max_string_param_length: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WarningsConfig {
+    /* 0 disables the warning */
+    pub slow_callback_ms: u64,
+    pub runaway_executions: u64,
+}
+
+impl Default for WarningsConfig {
+    fn default() -> Self {
+        WarningsConfig {
+            slow_callback_ms: 10,
+            runaway_executions: 1_000_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Plain,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Never,
+    Hourly,
+    Daily,
+    Weekly,
+    Size, /* once the file reaches log.max_size_mb */
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /* empty disables the log file */
+    pub file: String,
+    pub level: String,
+    pub format: LogFormat,
+    pub rotation: LogRotation,
+    pub max_size_mb: u64,
+    /* rotated files to keep, 0 keeps all of them */
+    pub max_files: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            file: "logs/timers.log".to_string(),
+            level: "info".to_string(),
+            format: LogFormat::Plain,
+            rotation: LogRotation::Daily,
+            max_size_mb: 10,
+            max_files: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerLogConfig {
+    /* "off" keeps server_log.txt free of plugin messages */
+    pub level: String,
+    /* repeated messages about the same timer are logged once per window, 0 logs all of them */
+    pub rate_limit_secs: u64,
+}
+
+impl Default for ServerLogConfig {
+    fn default() -> Self {
+        ServerLogConfig {
+            level: "warn".to_string(),
+            rate_limit_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /* only used when built with the metrics feature, empty disables the listener */
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    pub listen: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            listen: "127.0.0.1:9464".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DumpConfig {
+    /* written on unload and on panic, empty disables the automatic dump */
+    pub file: String,
+}
+
+impl Default for DumpConfig {
+    fn default() -> Self {
+        DumpConfig {
+            file: "logs/timers.dump.json".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PersistenceConfig {
+    /* empty disables persistent timers */
+    pub file: String,
+    /* also saved whenever a script unloads, 0 saves only then */
+    pub save_interval_secs: u64,
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        PersistenceConfig {
+            file: "timers.state.json".to_string(),
+            save_interval_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CronConfig {
+    /* "local", "utc" or "+07:00", scripts can override it with Timer_SetCronTimezone */
+    pub timezone: String,
+}
+
+impl Default for CronConfig {
+    fn default() -> Self {
+        CronConfig {
+            timezone: "local".to_string(),
+        }
+    }
+}
+
+impl CronConfig {
+    /* checked in validate, the fallback only covers an unvalidated config */
+    pub fn timezone(&self) -> CronTimezone {
+        CronTimezone::parse(&self.timezone).unwrap_or(CronTimezone::Local)
+    }
+}
+
+impl Config {
+    /* a missing file is not an error, every setting has a default */
+    pub fn load(path: &Path) -> TimerResult<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => {
+                return Err(TimerError::InvalidConfig(format!("Failed to read {}: {}", path.display(), e)));
+            }
+        };
+
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> TimerResult<Self> {
+        let config: Config = toml::from_str(contents)
+            .map_err(|e| TimerError::InvalidConfig(e.to_string().trim_end().to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> TimerResult<()> {
+        let mut problems = Vec::new();
+
+        let mut check = |ok: bool, key: &str, value: u64, range: &str| {
+            if !ok {
+                problems.push(format!("{} = {} (expected {})", key, value, range));
+            }
+        };
+
+        check((1..=64).contains(&self.runtime.worker_threads), "runtime.worker_threads", self.runtime.worker_threads as u64, "1-64");
+        check((1..=1_000_000).contains(&self.limits.max_timers), "limits.max_timers", self.limits.max_timers as u64, "1-1000000");
+        check(self.limits.max_timers_per_script <= 1_000_000, "limits.max_timers_per_script", self.limits.max_timers_per_script as u64, "0-1000000");
+        check(self.limits.max_timers_per_group <= 1_000_000, "limits.max_timers_per_group", self.limits.max_timers_per_group as u64, "0-1000000");
+        check((1..=64).contains(&self.limits.max_callback_params), "limits.max_callback_params", self.limits.max_callback_params as u64, "1-64");
+        check(
+            (1..=65536).contains(&self.limits.max_string_param_length),
+            "limits.max_string_param_length",
+            self.limits.max_string_param_length as u64,
+            "1-65536",
+        );
+
+        check((1..=4096).contains(&self.log.max_size_mb), "log.max_size_mb", self.log.max_size_mb, "1-4096");
+
+        for (key, level) in [("log.level", &self.log.level), ("server_log.level", &self.server_log.level)] {
+            if level.parse::<LevelFilter>().is_err() {
+                problems.push(format!("{} = \"{}\" (expected off, error, warn, info, debug or trace)", key, level));
+            }
+        }
+
+        if !self.metrics.listen.is_empty() && self.metrics.listen.parse::<std::net::SocketAddr>().is_err() {
+            problems.push(format!("metrics.listen = \"{}\" (expected an address like 127.0.0.1:9464)", self.metrics.listen));
+        }
+
+        if CronTimezone::parse(&self.cron.timezone).is_err() {
+            problems.push(format!("cron.timezone = \"{}\" (expected local, utc or an offset like +07:00)", self.cron.timezone));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(TimerError::InvalidConfig(problems.join(", ")))
+        }
+    }
+}
+
+/* applied once when the plugin loads, before any timer exists */
+pub fn init(config: Config) {
+    if CONFIG.set(config).is_err() {
+        tracing::warn!("Timer configuration already initialized, ignoring");
+    }
+}
+
+/* the active configuration, defaults until init has been called */
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.runtime.worker_threads, 2);
+        assert_eq!(config.limits.max_timers, 10000);
+
+        let config = Config::parse("[limits]\nmax_timers = 500\nmax_timers_per_script = 50\n\n[warnings]\nslow_callback_ms = 0\n").unwrap();
+        assert_eq!(config.limits.max_timers, 500);
+        assert_eq!(config.limits.max_timers_per_script, 50);
+        assert_eq!(config.limits.max_timers_per_group, 0);
+        assert_eq!(config.limits.max_callback_params, 16);
+        assert_eq!(config.warnings.slow_callback_ms, 0);
+
+        let config = Config::parse("[log]\nformat = \"json\"\nrotation = \"hourly\"\nlevel = \"debug\"\n").unwrap();
+        assert_eq!(config.log.format, LogFormat::Json);
+        assert_eq!(config.log.rotation, LogRotation::Hourly);
+        assert_eq!(config.log.file, "logs/timers.log");
+
+        let config = Config::parse("[log]\nrotation = \"size\"\nmax_size_mb = 50\n").unwrap();
+        assert_eq!(config.log.rotation, LogRotation::Size);
+        assert_eq!(config.log.max_size_mb, 50);
+
+        assert_eq!(Config::parse("").unwrap().cron.timezone(), CronTimezone::Local);
+        let config = Config::parse("[cron]\ntimezone = \"+07:00\"\n").unwrap();
+        assert_eq!(config.cron.timezone(), CronTimezone::Fixed(420));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(matches!(Config::parse("[limits]\nmax_timer = 500\n"), Err(TimerError::InvalidConfig(_))));
+        assert!(matches!(Config::parse("[runtime]\nworker_threads = \"two\"\n"), Err(TimerError::InvalidConfig(_))));
+        assert!(matches!(Config::parse("[log]\nformat = \"xml\"\n"), Err(TimerError::InvalidConfig(_))));
+        assert!(matches!(Config::parse("[log]\nlevel = \"verbose\"\n"), Err(TimerError::InvalidConfig(_))));
+        assert!(matches!(Config::parse("[server_log]\nlevel = \"warning\"\n"), Err(TimerError::InvalidConfig(_))));
+        assert!(matches!(Config::parse("[metrics]\nlisten = \"localhost\"\n"), Err(TimerError::InvalidConfig(_))));
+        assert!(matches!(Config::parse("[cron]\ntimezone = \"Asia/Jakarta\"\n"), Err(TimerError::InvalidConfig(_))));
+
+        match Config::parse("[runtime]\nworker_threads = 0\n[limits]\nmax_timers = 0\n") {
+            Err(TimerError::InvalidConfig(message)) => {
+                assert!(message.contains("runtime.worker_threads = 0"));
+                assert!(message.contains("limits.max_timers = 0"));
+            }
+            other => panic!("Expected invalid config, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::error::{TimerError, TimerResult};
+
+/* how far ahead to look for a match, long enough for Feb 29 schedules */
+const MAX_SEARCH_DAYS: u32 = 366 * 5;
+const MAX_UTC_OFFSET_MINUTES: i32 = 18 * 60;
+const LOCAL_TIMEZONE: i32 = i32::MIN; /* TIMER_TZ_LOCAL */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CronTimezone {
+    Local, /* server time zone, including its DST rules */
+    Fixed(i32), /* minutes east of UTC */
+}
+
+impl CronTimezone {
+    pub fn from_offset_minutes(offset_minutes: i32) -> TimerResult<Self> {
+        if offset_minutes == LOCAL_TIMEZONE {
+            return Ok(CronTimezone::Local);
+        }
+
+        if offset_minutes.abs() > MAX_UTC_OFFSET_MINUTES {
+            return Err(TimerError::ParameterValidation(
+                format!("UTC offset out of range: {} minutes (max ±{})", offset_minutes, MAX_UTC_OFFSET_MINUTES)
+            ));
+        }
+
+        Ok(CronTimezone::Fixed(offset_minutes))
+    }
+
+    /* "local", "utc" or an offset like "+07:00" / "-03:30", as written in timers.toml */
+    pub fn parse(value: &str) -> TimerResult<Self> {
+        let invalid = || TimerError::ParameterValidation(
+            format!("Invalid time zone '{}', expected local, utc or an offset like +07:00", value)
+        );
+
+        match value.trim().to_ascii_lowercase().as_str() {
+            "local" => Ok(CronTimezone::Local),
+            "utc" => Ok(CronTimezone::Fixed(0)),
+            offset => {
+                let (sign, rest) = match offset.split_at_checked(1) {
+                    Some(("+", rest)) => (1, rest),
+                    Some(("-", rest)) => (-1, rest),
+                    _ => return Err(invalid()),
+                };
+                let (hours, minutes) = rest.split_once(':').ok_or_else(invalid)?;
+                let number = |field: &str| match field.len() {
+                    1 | 2 if field.bytes().all(|b| b.is_ascii_digit()) => field.parse::<i32>().map_err(|_| invalid()),
+                    _ => Err(invalid()),
+                };
+                let (hours, minutes) = (number(hours)?, number(minutes)?);
+                if minutes >= 60 {
+                    return Err(invalid());
+                }
+                Self::from_offset_minutes(sign * (hours * 60 + minutes))
+            }
+        }
+    }
+}
+
+/* parsed "minute hour day-of-month month day-of-week" expression, one bit per allowed value */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    /* when both day fields are restricted, either may match (Vixie cron semantics) */
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+    pub timezone: CronTimezone,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str, timezone: CronTimezone) -> TimerResult<Self> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(Self::parse_error(expression, format!("expected 5 fields, got {}", fields.len())));
+        }
+
+        let minutes = parse_field(fields[0], 0, 59).map_err(|e| Self::parse_error(expression, e))?;
+        let hours = parse_field(fields[1], 0, 23).map_err(|e| Self::parse_error(expression, e))?;
+        let days_of_month = parse_field(fields[2], 1, 31).map_err(|e| Self::parse_error(expression, e))?;
+        let months = parse_field(fields[3], 1, 12).map_err(|e| Self::parse_error(expression, e))?;
+        let mut days_of_week = parse_field(fields[4], 0, 7).map_err(|e| Self::parse_error(expression, e))?;
+
+        /* 7 is an alias for Sunday */
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(CronSchedule {
+            minutes,
+            hours: hours as u32,
+            days_of_month: days_of_month as u32,
+            months: months as u16,
+            days_of_week: days_of_week as u8,
+            day_of_month_restricted: !fields[2].starts_with('*'),
+            day_of_week_restricted: !fields[4].starts_with('*'),
+            timezone,
+        })
+    }
+
+    fn parse_error(expression: &str, reason: String) -> TimerError {
+        TimerError::ParameterParseError(format!("Invalid cron expression '{}': {}", expression, reason))
+    }
+
+    /* first matching instant strictly after `after` */
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.timezone {
+            CronTimezone::Local => self.next_after_in(after, &Local),
+            CronTimezone::Fixed(offset_minutes) => {
+                let offset = FixedOffset::east_opt(offset_minutes * 60)?;
+                self.next_after_in(after, &offset)
+            }
+        }
+    }
+
+    pub fn next_after_in<Tz: TimeZone>(&self, after: DateTime<Utc>, timezone: &Tz) -> Option<DateTime<Utc>> {
+        let mut date = after.with_timezone(timezone).date_naive();
+
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.matches_date(date) {
+                for hour in (0..24).filter(|hour| self.hours & (1 << hour) != 0) {
+                    for minute in (0..60).filter(|minute| self.minutes & (1 << minute) != 0) {
+                        let naive = date.and_hms_opt(hour, minute, 0)?;
+                        if let Some(candidate) = resolve_local(&naive, timezone, after) {
+                            return Some(candidate);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+
+        let day_of_month = self.days_of_month & (1 << date.day()) != 0;
+        let day_of_week = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+
+        if self.day_of_month_restricted && self.day_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+/*
+ * Maps a wall-clock time to the instant it fires at, if that is after `after`.
+ * Times repeated when clocks go back fire once, on their first occurrence.
+ * Times skipped when clocks go forward fire as soon as the clock resumes.
+ */
+fn resolve_local<Tz: TimeZone>(naive: &NaiveDateTime, timezone: &Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let candidate = match timezone.from_local_datetime(naive) {
+        LocalResult::Single(time) => time.with_timezone(&Utc),
+        LocalResult::Ambiguous(first, _) => first.with_timezone(&Utc),
+        LocalResult::None => (1..=24 * 60)
+            .filter_map(|minutes| {
+                timezone.from_local_datetime(&(*naive + ChronoDuration::minutes(minutes))).earliest()
+            })
+            .next()?
+            .with_timezone(&Utc),
+    };
+
+    (candidate > after).then_some(candidate)
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step must be positive".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start)?, parse_value(end)?)
+        } else {
+            let value = parse_value(range)?;
+            /* "5/15" means every 15 starting at 5 */
+            if part.contains('/') { (value, max) } else { (value, value) }
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!("'{}' out of range {}-{}", part, min, max));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Ok(bits)
+}
+
+fn parse_value(value: &str) -> Result<u32, String> {
+    value.parse().map_err(|_| format!("invalid value '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, NaiveDate, NaiveTime, Offset};
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn schedule(expression: &str) -> CronSchedule {
+        CronSchedule::parse(expression, CronTimezone::Fixed(0)).expect("Failed to parse cron expression")
+    }
+
+    /* UTC+1 in winter, UTC+2 from 2024-03-31 01:00 UTC to 2024-10-27 01:00 UTC, like Central Europe */
+    #[derive(Debug, Clone, Copy)]
+    struct TestDst;
+
+    impl TestDst {
+        fn offset_at(utc: &NaiveDateTime) -> FixedOffset {
+            let summer_start = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            let summer_end = NaiveDate::from_ymd_opt(2024, 10, 27).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            let hours = if *utc >= summer_start && *utc < summer_end { 2 } else { 1 };
+            FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    impl TimeZone for TestDst {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            TestDst
+        }
+
+        /* dates are resolved at midnight, as chrono does for its own time zones */
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let candidates: Vec<FixedOffset> = [1, 2]
+                .iter()
+                .map(|hours| FixedOffset::east_opt(hours * 3600).unwrap())
+                .filter(|offset| Self::offset_at(&(*local - *offset)).fix() == *offset)
+                .collect();
+
+            match candidates.as_slice() {
+                [] => LocalResult::None,
+                [offset] => LocalResult::Single(*offset),
+                [winter, summer] => LocalResult::Ambiguous(*summer, *winter),
+                _ => unreachable!(),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            Self::offset_at(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Self::offset_at(utc)
+        }
+    }
+
+    #[test]
+    fn test_parse_cron_expressions() {
+        assert!(CronSchedule::parse("* * * * *", CronTimezone::Local).is_ok());
+        assert!(CronSchedule::parse("0 */1 * * *", CronTimezone::Local).is_ok());
+        assert!(CronSchedule::parse("0,30 8-18/2 1,15 * 1-5", CronTimezone::Local).is_ok());
+        assert!(CronSchedule::parse("@daily", CronTimezone::Local).is_ok());
+        assert_eq!(schedule("0 0 * * 7"), schedule("0 0 * * 0"));
+        assert_eq!(schedule("@hourly"), schedule("0 * * * *"));
+
+        assert!(matches!(CronSchedule::parse("* * * *", CronTimezone::Local), Err(TimerError::ParameterParseError(_))));
+        assert!(matches!(CronSchedule::parse("60 * * * *", CronTimezone::Local), Err(TimerError::ParameterParseError(_))));
+        assert!(matches!(CronSchedule::parse("* 24 * * *", CronTimezone::Local), Err(TimerError::ParameterParseError(_))));
+        assert!(matches!(CronSchedule::parse("* * 0 * *", CronTimezone::Local), Err(TimerError::ParameterParseError(_))));
+        assert!(matches!(CronSchedule::parse("*/0 * * * *", CronTimezone::Local), Err(TimerError::ParameterParseError(_))));
+        assert!(matches!(CronSchedule::parse("5-1 * * * *", CronTimezone::Local), Err(TimerError::ParameterParseError(_))));
+        assert!(matches!(CronSchedule::parse("a * * * *", CronTimezone::Local), Err(TimerError::ParameterParseError(_))));
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(CronTimezone::parse("local").unwrap(), CronTimezone::Local);
+        assert_eq!(CronTimezone::parse("UTC").unwrap(), CronTimezone::Fixed(0));
+        assert_eq!(CronTimezone::parse("+07:00").unwrap(), CronTimezone::Fixed(420));
+        assert_eq!(CronTimezone::parse("-03:30").unwrap(), CronTimezone::Fixed(-210));
+
+        for value in ["", "7", "+7", "+07:60", "+19:00", "-+1:00", "Asia/Jakarta"] {
+            assert!(CronTimezone::parse(value).is_err(), "'{}' should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_next_fire_basic() {
+        let hourly = schedule("0 */1 * * *");
+        assert_eq!(hourly.next_after(utc(2024, 5, 10, 13, 20)), Some(utc(2024, 5, 10, 14, 0)));
+        /* strictly after: a schedule firing now moves to the next occurrence */
+        assert_eq!(hourly.next_after(utc(2024, 5, 10, 14, 0)), Some(utc(2024, 5, 10, 15, 0)));
+
+        let daily = schedule("0 4 * * *");
+        assert_eq!(daily.next_after(utc(2024, 12, 31, 5, 0)), Some(utc(2025, 1, 1, 4, 0)));
+
+        let stepped = schedule("5/15 * * * *");
+        assert_eq!(stepped.next_after(utc(2024, 5, 10, 13, 21)), Some(utc(2024, 5, 10, 13, 35)));
+        assert_eq!(stepped.next_after(utc(2024, 5, 10, 13, 50)), Some(utc(2024, 5, 10, 14, 5)));
+    }
+
+    #[test]
+    fn test_next_fire_day_fields() {
+        /* 2024-05-10 is a Friday; weekend bonus starts Saturday */
+        let weekend = schedule("0 18 * * 6,0");
+        assert_eq!(weekend.next_after(utc(2024, 5, 10, 12, 0)), Some(utc(2024, 5, 11, 18, 0)));
+        assert_eq!(weekend.next_after(utc(2024, 5, 11, 18, 0)), Some(utc(2024, 5, 12, 18, 0)));
+
+        /* both day fields restricted: the 15th or any Monday */
+        let either = schedule("0 0 15 * 1");
+        assert_eq!(either.next_after(utc(2024, 5, 10, 12, 0)), Some(utc(2024, 5, 13, 0, 0)));
+        assert_eq!(either.next_after(utc(2024, 5, 13, 0, 0)), Some(utc(2024, 5, 15, 0, 0)));
+
+        let leap_day = schedule("0 12 29 2 *");
+        assert_eq!(leap_day.next_after(utc(2024, 3, 1, 0, 0)), Some(utc(2028, 2, 29, 12, 0)));
+
+        assert_eq!(schedule("0 0 30 2 *").next_after(utc(2024, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_next_fire_with_fixed_offset() {
+        let restart = CronSchedule::parse("0 4 * * *", CronTimezone::Fixed(7 * 60)).expect("Failed to parse cron expression");
+        /* 04:00 at UTC+7 is 21:00 UTC the day before */
+        assert_eq!(restart.next_after(utc(2024, 5, 10, 12, 0)), Some(utc(2024, 5, 10, 21, 0)));
+        assert_eq!(restart.next_after(utc(2024, 5, 10, 21, 0)), Some(utc(2024, 5, 11, 21, 0)));
+
+        assert!(CronTimezone::from_offset_minutes(19 * 60).is_err());
+        assert_eq!(CronTimezone::from_offset_minutes(LOCAL_TIMEZONE).ok(), Some(CronTimezone::Local));
+    }
+
+    #[test]
+    fn test_next_fire_across_dst_changes() {
+        /* 02:30 doesn't exist on 2024-03-31: fire when clocks jump to 03:00 (01:00 UTC) */
+        let skipped = schedule("30 2 * * *");
+        assert_eq!(skipped.next_after_in(utc(2024, 3, 30, 23, 0), &TestDst), Some(utc(2024, 3, 31, 1, 0)));
+        assert_eq!(skipped.next_after_in(utc(2024, 3, 31, 1, 0), &TestDst), Some(utc(2024, 4, 1, 0, 30)));
+
+        /* 02:30 happens twice on 2024-10-27: fire only on the first pass */
+        let repeated = schedule("30 2 * * *");
+        assert_eq!(repeated.next_after_in(utc(2024, 10, 26, 12, 0), &TestDst), Some(utc(2024, 10, 27, 0, 30)));
+        assert_eq!(repeated.next_after_in(utc(2024, 10, 27, 0, 30), &TestDst), Some(utc(2024, 10, 28, 1, 30)));
+        /* re-checking during the repeated hour must not fire again */
+        assert_eq!(repeated.next_after_in(utc(2024, 10, 27, 1, 10), &TestDst), Some(utc(2024, 10, 28, 1, 30)));
+
+        /* the daily restart keeps its wall-clock time across the change */
+        let restart = schedule("0 4 * * *");
+        assert_eq!(restart.next_after_in(utc(2024, 3, 30, 4, 0), &TestDst), Some(utc(2024, 3, 31, 2, 0)));
+
+        let winter = FixedOffset::east_opt(3600).unwrap();
+        let summer = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(TestDst.offset_from_utc_date(&NaiveDate::from_ymd_opt(2024, 7, 1).unwrap()), summer);
+        assert_eq!(TestDst.offset_from_local_date(&NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()), LocalResult::Single(winter));
+    }
+}
@@ -0,0 +1,167 @@
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::callback::CallbackData;
+use crate::error::{TimerError, TimerResult};
+use crate::timer::{ScheduleMode, Timer};
+
+/* state of one timer when the dump was taken */
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerSnapshot {
+    pub id: i32,
+    pub callback: String,
+    pub params: Option<CallbackData>,
+    pub owner: String,
+    pub delay_ms: u64,
+    pub initial_delay_ms: u64,
+    pub repeat: bool,
+    pub schedule: &'static str,
+    pub paused: bool,
+    pub group: Option<i32>,
+    pub player: Option<i32>,
+    pub execution_count: u64,
+    pub failure_count: u64,
+    pub executions_left: Option<u64>,
+    pub remaining_ms: u64,
+    /* unix time in milliseconds, null while paused */
+    pub next_deadline: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl TimerSnapshot {
+    pub fn new(timer: &Timer) -> Self {
+        let stats = timer.stats.summary();
+        let remaining = timer.remaining();
+        let next_deadline = (!timer.is_paused())
+            .then(|| Utc::now().timestamp_millis().saturating_add(remaining.as_millis() as i64));
+
+        TimerSnapshot {
+            id: timer.id,
+            callback: timer.callback.clone(),
+            params: timer.params.clone(),
+            owner: format!("{:#x}", timer.owner_amx),
+            delay_ms: timer.delay_ms,
+            initial_delay_ms: timer.initial_delay_ms,
+            repeat: timer.repeat,
+            schedule: match timer.schedule {
+                ScheduleMode::FixedDelay => "fixed_delay",
+                ScheduleMode::FixedRate(_) => "fixed_rate",
+                ScheduleMode::Cron(_) => "cron",
+            },
+            paused: timer.is_paused(),
+            group: timer.group,
+            player: timer.player,
+            execution_count: timer.execution_count,
+            failure_count: stats.failures,
+            executions_left: timer.executions_left,
+            remaining_ms: duration_to_ms(remaining),
+            next_deadline,
+            last_error: stats.last_error,
+        }
+    }
+}
+
+fn duration_to_ms(duration: Duration) -> u64 {
+    duration.as_millis().min(u64::MAX as u128) as u64
+}
+
+#[derive(Serialize)]
+struct Dump<'a> {
+    version: &'static str,
+    generated_at: String,
+    timers: &'a [TimerSnapshot],
+}
+
+pub fn to_json(timers: &[TimerSnapshot]) -> TimerResult<String> {
+    let dump = Dump {
+        version: env!("CARGO_PKG_VERSION"),
+        generated_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        timers,
+    };
+
+    serde_json::to_string_pretty(&dump)
+        .map_err(|e| TimerError::Internal(format!("Failed to serialize timers: {}", e)))
+}
+
+/* same sandbox as the Pawn file natives */
+pub const SCRIPT_DIRECTORY: &str = "scriptfiles";
+
+/* resolves a path given by a script inside SCRIPT_DIRECTORY */
+pub fn script_path(path: &str) -> TimerResult<PathBuf> {
+    let relative = Path::new(path);
+    let escapes = relative.components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+
+    if path.is_empty() || relative.has_root() || escapes {
+        return Err(TimerError::ParameterValidation(format!(
+            "Dump path '{}' must be relative to {} without '..'",
+            path, SCRIPT_DIRECTORY
+        )));
+    }
+    Ok(Path::new(SCRIPT_DIRECTORY).join(relative))
+}
+
+pub fn write_file(path: &Path, timers: &[TimerSnapshot]) -> TimerResult<()> {
+    write_atomic(path, &to_json(timers)?)
+}
+
+/* written to a temporary file first, so a crash while writing leaves the previous file intact */
+pub fn write_atomic(path: &Path, contents: &str) -> TimerResult<()> {
+    let file_error = |e: std::io::Error| TimerError::FileError(format!("{}: {}", path.display(), e));
+
+    if let Some(directory) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(directory).map_err(file_error)?;
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    std::fs::write(&temp_path, contents).map_err(file_error)?;
+    std::fs::rename(&temp_path, path).map_err(file_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callback::CallbackParam;
+    use crate::timer::TimerOptions;
+
+    #[test]
+    fn test_timer_snapshot_json() {
+        let mut params = CallbackData::new();
+        params.add_param(CallbackParam::Integer(7)).expect("Failed to add integer param");
+        params.add_param(CallbackParam::String("jail".to_string())).expect("Failed to add string param");
+
+        let mut timer = Timer::new(0x1234, 5000, true, "OnJailTick".to_string(), Some(params), TimerOptions::default())
+            .expect("Failed to create timer");
+        timer.group = Some(3);
+        timer.pause();
+
+        let json = to_json(&[TimerSnapshot::new(&timer)]).expect("Failed to serialize timers");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("Dump is not valid JSON");
+        let snapshot = &value["timers"][0];
+
+        assert_eq!(snapshot["callback"], "OnJailTick");
+        assert_eq!(snapshot["owner"], "0x1234");
+        assert_eq!(snapshot["params"][0], serde_json::json!({"type": "integer", "value": 7}));
+        assert_eq!(snapshot["params"][1], serde_json::json!({"type": "string", "value": "jail"}));
+        assert_eq!(snapshot["group"], 3);
+        assert_eq!(snapshot["paused"], true);
+        assert!(snapshot["next_deadline"].is_null());
+        assert!(snapshot["remaining_ms"].as_u64().is_some_and(|remaining| remaining <= 5000));
+    }
+
+    #[test]
+    fn test_script_path() {
+        assert_eq!(script_path("timers.json").unwrap(), Path::new("scriptfiles/timers.json"));
+        assert_eq!(script_path("dumps/./timers.json").unwrap(), Path::new("scriptfiles/dumps/./timers.json"));
+
+        for path in ["", "/etc/passwd", "../server.cfg", "dumps/../../samp.exe"] {
+            assert!(
+                matches!(script_path(path), Err(TimerError::ParameterValidation(_))),
+                "'{}' should be rejected",
+                path
+            );
+        }
+    }
+}
@@ -32,6 +32,24 @@ pub enum TimerError {
     #[error("Parameter validation failed: {0}")]
     ParameterValidation(String),
 
+    #[error("Timestamp {0} is in the past")]
+    TimestampInPast(i64),
+
+    #[error("Script timer quota reached ({0} timers)")]
+    ScriptQuotaExceeded(usize),
+
+    #[error("Group timer quota reached ({0} timers)")]
+    GroupQuotaExceeded(usize),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("File error: {0}")]
+    FileError(String),
+
+    #[error("Timer {0} belongs to another script")]
+    NotOwner(i32),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -49,6 +67,12 @@ impl TimerError {
             TimerError::IdOverflow => -8,
             TimerError::ResourceExhaustion(_) => -10,
             TimerError::ParameterValidation(_) => -11,
+            TimerError::TimestampInPast(_) => -12,
+            TimerError::ScriptQuotaExceeded(_) => -13,
+            TimerError::GroupQuotaExceeded(_) => -14,
+            TimerError::InvalidConfig(_) => -15,
+            TimerError::FileError(_) => -16,
+            TimerError::NotOwner(_) => -17,
             TimerError::Internal(_) => -99,
         }
     }
@@ -62,7 +86,9 @@ impl TimerError {
             TimerError::InvalidDelay(_)
             | TimerError::InvalidCallback(_)
             | TimerError::ParameterParseError(_)
-            | TimerError::ParameterValidation(_) => false,
+            | TimerError::ParameterValidation(_)
+            | TimerError::TimestampInPast(_)
+            | TimerError::NotOwner(_) => false,
 
             TimerError::TimerNotFound(_)
             | TimerError::CallbackExecutionError(_) => true,
@@ -71,6 +97,10 @@ impl TimerError {
             | TimerError::TaskSpawnError(_)
             | TimerError::IdOverflow
             | TimerError::ResourceExhaustion(_)
+            | TimerError::ScriptQuotaExceeded(_)
+            | TimerError::GroupQuotaExceeded(_)
+            | TimerError::InvalidConfig(_)
+            | TimerError::FileError(_)
             | TimerError::Internal(_) => false,
         }
     }
@@ -1,17 +1,31 @@
 use samp::prelude::*;
+use samp::args::Args;
 use samp::{initialize_plugin, native};
 
 mod timer;
 mod error;
 mod callback;
 mod amx_manager;
+mod cron;
+mod config;
+mod logging;
+mod server_log;
+mod rcon;
+mod dump;
+mod persistence;
+#[cfg(feature = "metrics")]
+mod metrics;
 
-use timer::TimerManager;
+use timer::{MissedTickPolicy, PersistMode, QuotaKind, ScheduleMode, TimerManager, TimerOptions};
 use error::{TimerError, TimerResult};
 use amx_manager::AmxManager;
+use rcon::RconCommand;
+use persistence::PersistentStore;
 
 pub struct TimerPlugin {
     timer_manager: TimerManager,
+    persistence: PersistentStore,
+    log_guard: Option<tracing_appender::non_blocking::WorkerGuard>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -55,31 +69,180 @@ fn build_callback_data(param_type: TimerParamType, int_param: i32, float_param:
             }
             callback::CallbackParam::Float(float_param)
         },
-        TimerParamType::String => {
-            if string_param.len() > 1024 {
-                return Err(TimerError::ParameterValidation("String parameter too long".to_string()));
-            }
-            callback::CallbackParam::String(string_param.to_string())
-        },
+        /* length is checked against limits.max_string_param_length by add_param */
+        TimerParamType::String => callback::CallbackParam::String(string_param.to_string()),
     };
 
     callback_data.add_param(param)?;
     Ok(callback_data)
 }
 
+fn duration_to_ms(duration: std::time::Duration) -> i32 {
+    duration.as_millis().min(i32::MAX as u128) as i32
+}
+
+fn duration_to_us(duration: std::time::Duration) -> i32 {
+    duration.as_micros().min(i32::MAX as u128) as i32
+}
+
+/* copies as much of the string as fits, always null-terminated */
+fn put_string(buffer: UnsizedBuffer, size: i32, value: &str) -> bool {
+    if size <= 0 {
+        return false;
+    }
+
+    let mut buffer = buffer.into_sized_buffer(size as usize);
+    let mut end = value.len().min(size as usize - 1);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    samp::cell::string::put_in_buffer(&mut buffer, &value[..end]).is_ok()
+}
+
+/* the fixed arguments (delay, repeat, callback, format or their equivalents) preceding variadic parameters */
+const FORMAT_FIXED_ARGS: usize = 4;
+/* expression, callback, format */
+const CRON_FIXED_ARGS: usize = 3;
+/* playerid, delay, repeat, callback, format */
+const PLAYER_FIXED_ARGS: usize = 5;
+
+/* passed to Timer_SetGroup to take a timer out of its group */
+const TIMER_NO_GROUP: i32 = -1;
+
+/* passed to Timer_SetPersistent to stop saving a timer */
+const TIMER_PERSIST_NONE: i32 = 0;
+
+/* returned by Timer_GetRemainingCount for timers without a limit, cellmax so it can't be mistaken for an error code */
+const TIMER_COUNT_UNLIMITED: i32 = i32::MAX;
+
+fn build_callback_data_from_format(format: &str, args: &mut Args, fixed_args: usize) -> TimerResult<callback::CallbackData> {
+    let specifiers = callback::parse_format(format)?;
+
+    let provided = args.count().saturating_sub(fixed_args);
+    if provided != specifiers.len() {
+        return Err(TimerError::ParameterParseError(
+            format!("Format '{}' expects {} arguments, got {}", format, specifiers.len(), provided)
+        ));
+    }
+
+    let mut callback_data = callback::CallbackData::with_capacity(specifiers.len());
+
+    /* variadic arguments are always passed by reference */
+    for (position, specifier) in specifiers.iter().enumerate() {
+        let offset = fixed_args + position;
+
+        let param = match specifier {
+            callback::FormatSpecifier::Integer => args.get::<Ref<i32>>(offset).map(|val| callback::CallbackParam::Integer(*val)),
+            callback::FormatSpecifier::Float => args.get::<Ref<f32>>(offset).map(|val| callback::CallbackParam::Float(*val)),
+            callback::FormatSpecifier::String => args.get::<AmxString>(offset).map(|val| callback::CallbackParam::String(val.to_string())),
+            callback::FormatSpecifier::Array => {
+                /* the array is copied now, the script may reuse its buffer before the timer fires */
+                let length = args.get::<Ref<i32>>(offset + 1).map(|val| *val);
+                match length {
+                    Some(length) if length < 0 || length as usize > callback::MAX_ARRAY_PARAM_LENGTH => {
+                        return Err(TimerError::ParameterValidation(
+                            format!("Array length out of range: {} (0..={})", length, callback::MAX_ARRAY_PARAM_LENGTH)
+                        ));
+                    }
+                    Some(length) => args.get::<UnsizedBuffer>(offset).map(|buffer| {
+                        callback::CallbackParam::Array(buffer.into_sized_buffer(length as usize).as_slice().to_vec())
+                    }),
+                    None => None,
+                }
+            }
+        };
+
+        let param = param.ok_or_else(|| TimerError::ParameterParseError(
+            format!("Failed to read argument {} for format '{}'", position + 1, format)
+        ))?;
+
+        callback_data.add_param(param)?;
+    }
+
+    Ok(callback_data)
+}
+
 impl SampPlugin for TimerPlugin {
     fn on_load(&mut self) {
         tracing::info!("Timers Plugin v1.0.2 has been loaded");
         tracing::info!("timer plugins initialized");
+
+        let config = config::get();
+        tracing::info!(
+            "Using {} worker threads, up to {} timers with {} parameters each",
+            config.runtime.worker_threads,
+            config.limits.max_timers,
+            config.limits.max_callback_params
+        );
+
+        #[cfg(feature = "metrics")]
+        if !config.metrics.listen.is_empty() {
+            if let Err(e) = self.timer_manager.start_metrics_listener(&config.metrics.listen) {
+                tracing::error!("Metrics listener disabled: {}", e);
+            }
+        }
     }
 
     fn on_unload(&mut self) {
         tracing::info!("SA-MP Timers Plugin unloading...");
+
+        match self.persistence.save(&self.timer_manager) {
+            Ok(count) if count > 0 => tracing::info!("Saved {} persistent timers", count),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to save persistent timers: {}", e),
+        }
+
+        let dump_file = &config::get().dump.file;
+        if !dump_file.is_empty() {
+            match self.timer_manager.dump_to_file(std::path::Path::new(dump_file)) {
+                Ok(count) => tracing::info!("Dumped {} timers to {}", count, dump_file),
+                Err(e) => tracing::error!("Failed to dump timers: {}", e),
+            }
+        }
+
+        self.timer_manager.restore_panic_hook();
         self.timer_manager.shutdown();
 
         AmxManager::clear_all_instances();
 
         tracing::info!("All timers stopped and cleaned up");
+
+        /* flushes the log files */
+        server_log::flush();
+        self.log_guard.take();
+    }
+
+    fn on_amx_load(&mut self, amx: &Amx) {
+        AmxManager::register_amx(amx);
+    }
+
+    fn on_amx_unload(&mut self, amx: &Amx) {
+        let amx_id = AmxManager::unregister_amx(amx);
+
+        /* persistent timers are kept before the script's timers are killed */
+        if self.persistence.unregister_script(&self.timer_manager, amx_id) > 0 {
+            if let Err(e) = self.persistence.save(&self.timer_manager) {
+                tracing::error!("Failed to save persistent timers: {}", e);
+            }
+        }
+
+        let reaped = self.timer_manager.kill_timers_for_amx(amx_id);
+        self.timer_manager.clear_cron_timezone(amx_id);
+        if reaped > 0 {
+            tracing::info!("AMX {:#x} unloaded, killed {} of its timers", amx_id, reaped);
+        } else {
+            tracing::debug!("AMX {:#x} unloaded with no active timers", amx_id);
+        }
+
+        #[cfg(feature = "metrics")]
+        metrics::script_unloaded(amx_id);
+    }
+
+    fn process_tick(&mut self) {
+        self.timer_manager.process_tick();
+        self.persistence.save_if_due(&self.timer_manager);
+        server_log::flush();
     }
 }
 
@@ -92,7 +255,7 @@ impl TimerPlugin {
         repeat: bool,
         callback: AmxString,
     ) -> AmxResult<i32> {
-        AmxManager::register_amx(amx);
+        let owner_amx = AmxManager::register_amx(amx);
         let callback_str = callback.to_string();
 
         if let Err(error) = validate_timer_params(delay_ms, &callback_str) {
@@ -100,7 +263,7 @@ impl TimerPlugin {
             return Ok(error.to_error_code());
         }
 
-        match self.timer_manager.create_timer(delay_ms, repeat, callback_str, None) {
+        match self.timer_manager.create_timer(owner_amx, delay_ms, repeat, callback_str, None) {
             Ok(timer_id) => {
                 tracing::debug!("Created timer {} with delay {}ms, repeat: {}", timer_id, delay_ms, repeat);
                 Ok(timer_id)
@@ -116,9 +279,10 @@ impl TimerPlugin {
         }
     }
     #[native(name = "Timer_SetEx")]
+    #[allow(clippy::too_many_arguments)]
     pub fn timer_set_ex(
         &mut self,
-        _amx: &Amx,
+        amx: &Amx,
         delay_ms: i32,
         repeat: bool,
         callback: AmxString,
@@ -127,6 +291,7 @@ impl TimerPlugin {
         float_param: f32,
         string_param: AmxString,
     ) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
         let callback_str = callback.to_string();
 
         if let Err(error) = validate_timer_params(delay_ms, &callback_str) {
@@ -151,7 +316,7 @@ impl TimerPlugin {
             }
         };
 
-        match self.timer_manager.create_timer(delay_ms, repeat, callback_str, Some(callback_data)) {
+        match self.timer_manager.create_timer(owner_amx, delay_ms, repeat, callback_str, Some(callback_data)) {
             Ok(timer_id) => {
                 tracing::debug!("Created timer {} with parameter type {}", timer_id, param_type);
                 Ok(timer_id)
@@ -169,17 +334,18 @@ impl TimerPlugin {
     #[native(name = "Timer_SetOnce")]
     pub fn timer_set_once(
         &mut self,
-        _amx: &Amx,
+        amx: &Amx,
         delay_ms: i32,
         callback: AmxString,
     ) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
         let callback_str = callback.to_string();
         if let Err(error) = validate_timer_params(delay_ms, &callback_str) {
             tracing::error!("Timer creation failed: {}", error);
             return Ok(error.to_error_code());
         }
 
-        match self.timer_manager.create_timer(delay_ms, false, callback_str, None) {
+        match self.timer_manager.create_timer(owner_amx, delay_ms, false, callback_str, None) {
             Ok(timer_id) => {
                 tracing::debug!("Created one-shot timer {} with delay {}ms", timer_id, delay_ms);
                 Ok(timer_id)
@@ -191,9 +357,10 @@ impl TimerPlugin {
         }
     }
     #[native(name = "Timer_SetOnceEx")]
+    #[allow(clippy::too_many_arguments)]
     pub fn timer_set_once_ex(
         &mut self,
-        _amx: &Amx,
+        amx: &Amx,
         delay_ms: i32,
         callback: AmxString,
         param_type: i32, /* 0=int, 1=float, 2=string */
@@ -201,6 +368,7 @@ impl TimerPlugin {
         float_param: f32,
         string_param: AmxString,
     ) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
         let callback_str = callback.to_string();
 
         if let Err(error) = validate_timer_params(delay_ms, &callback_str) {
@@ -225,7 +393,7 @@ impl TimerPlugin {
             }
         };
 
-        match self.timer_manager.create_timer(delay_ms, false, callback_str, Some(callback_data)) {
+        match self.timer_manager.create_timer(owner_amx, delay_ms, false, callback_str, Some(callback_data)) {
             Ok(timer_id) => {
                 tracing::debug!("Created one-shot timer {} with parameter type {}", timer_id, param_type);
                 Ok(timer_id)
@@ -236,6 +404,336 @@ impl TimerPlugin {
             }
         }
     }
+    #[native(name = "Timer_SetFmt", raw)]
+    pub fn timer_set_fmt(&mut self, amx: &Amx, mut args: Args) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
+
+        let (delay_ms, repeat, callback_str, format) = match (
+            args.next::<i32>(),
+            args.next::<bool>(),
+            args.next::<AmxString>(),
+            args.next::<AmxString>(),
+        ) {
+            (Some(delay_ms), Some(repeat), Some(callback), Some(format)) => {
+                (delay_ms, repeat, callback.to_string(), format.to_string())
+            }
+            _ => {
+                let error = TimerError::ParameterParseError("Expected delay, repeat, callback and format".to_string());
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        if let Err(error) = validate_timer_params(delay_ms, &callback_str) {
+            tracing::error!("Timer creation failed: {}", error);
+            return Ok(error.to_error_code());
+        }
+
+        let callback_data = match build_callback_data_from_format(&format, &mut args, FORMAT_FIXED_ARGS) {
+            Ok(data) => data,
+            Err(error) => {
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        match self.timer_manager.create_timer(owner_amx, delay_ms, repeat, callback_str, Some(callback_data)) {
+            Ok(timer_id) => {
+                tracing::debug!("Created timer {} with format '{}'", timer_id, format);
+                Ok(timer_id)
+            }
+            Err(e) => {
+                if e.is_recoverable() {
+                    tracing::warn!("Recoverable timer creation error with parameters: {}", e);
+                } else {
+                    tracing::error!("Fatal timer creation error with parameters: {}", e);
+                }
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_SetCount", raw)]
+    pub fn timer_set_count(&mut self, amx: &Amx, mut args: Args) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
+
+        let (delay_ms, count, callback_str, format) = match (
+            args.next::<i32>(),
+            args.next::<i32>(),
+            args.next::<AmxString>(),
+            args.next::<AmxString>(),
+        ) {
+            (Some(delay_ms), Some(count), Some(callback), Some(format)) => {
+                (delay_ms, count, callback.to_string(), format.to_string())
+            }
+            _ => {
+                let error = TimerError::ParameterParseError("Expected delay, count, callback and format".to_string());
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        if let Err(error) = validate_timer_params(delay_ms, &callback_str) {
+            tracing::error!("Timer creation failed: {}", error);
+            return Ok(error.to_error_code());
+        }
+
+        if count <= 0 {
+            let error = TimerError::ParameterValidation(format!("Execution count must be positive: {}", count));
+            tracing::error!("Timer creation failed: {}", error);
+            return Ok(error.to_error_code());
+        }
+
+        let callback_data = match build_callback_data_from_format(&format, &mut args, FORMAT_FIXED_ARGS) {
+            Ok(data) if data.params.is_empty() => None,
+            Ok(data) => Some(data),
+            Err(error) => {
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        let options = TimerOptions {
+            max_executions: Some(count as u64),
+            ..TimerOptions::default()
+        };
+
+        match self.timer_manager.create_timer_with_options(owner_amx, delay_ms, true, callback_str, callback_data, options) {
+            Ok(timer_id) => {
+                tracing::debug!("Created timer {} limited to {} executions", timer_id, count);
+                Ok(timer_id)
+            }
+            Err(e) => {
+                if e.is_recoverable() {
+                    tracing::warn!("Recoverable timer creation error: {}", e);
+                } else {
+                    tracing::error!("Fatal timer creation error: {}", e);
+                }
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_SetInitial", raw)]
+    pub fn timer_set_initial(&mut self, amx: &Amx, mut args: Args) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
+
+        let (initial_delay_ms, interval_ms, callback_str, format) = match (
+            args.next::<i32>(),
+            args.next::<i32>(),
+            args.next::<AmxString>(),
+            args.next::<AmxString>(),
+        ) {
+            (Some(initial_delay_ms), Some(interval_ms), Some(callback), Some(format)) => {
+                (initial_delay_ms, interval_ms, callback.to_string(), format.to_string())
+            }
+            _ => {
+                let error = TimerError::ParameterParseError("Expected initial delay, interval, callback and format".to_string());
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        if let Err(error) = validate_timer_params(interval_ms, &callback_str) {
+            tracing::error!("Timer creation failed: {}", error);
+            return Ok(error.to_error_code());
+        }
+
+        if initial_delay_ms < 0 {
+            let error = TimerError::InvalidDelay(initial_delay_ms);
+            tracing::error!("Timer creation failed: {}", error);
+            return Ok(error.to_error_code());
+        }
+
+        let callback_data = match build_callback_data_from_format(&format, &mut args, FORMAT_FIXED_ARGS) {
+            Ok(data) if data.params.is_empty() => None,
+            Ok(data) => Some(data),
+            Err(error) => {
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        let options = TimerOptions {
+            initial_delay_ms: Some(initial_delay_ms as u64),
+            ..TimerOptions::default()
+        };
+
+        match self.timer_manager.create_timer_with_options(owner_amx, interval_ms, true, callback_str, callback_data, options) {
+            Ok(timer_id) => {
+                tracing::debug!("Created timer {} firing after {}ms, then every {}ms", timer_id, initial_delay_ms, interval_ms);
+                Ok(timer_id)
+            }
+            Err(e) => {
+                if e.is_recoverable() {
+                    tracing::warn!("Recoverable timer creation error: {}", e);
+                } else {
+                    tracing::error!("Fatal timer creation error: {}", e);
+                }
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_SetForPlayer", raw)]
+    pub fn timer_set_for_player(&mut self, amx: &Amx, mut args: Args) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
+
+        let (playerid, delay_ms, repeat, callback_str, format) = match (
+            args.next::<i32>(),
+            args.next::<i32>(),
+            args.next::<bool>(),
+            args.next::<AmxString>(),
+            args.next::<AmxString>(),
+        ) {
+            (Some(playerid), Some(delay_ms), Some(repeat), Some(callback), Some(format)) => {
+                (playerid, delay_ms, repeat, callback.to_string(), format.to_string())
+            }
+            _ => {
+                let error = TimerError::ParameterParseError("Expected playerid, delay, repeat, callback and format".to_string());
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        if let Err(error) = validate_timer_params(delay_ms, &callback_str) {
+            tracing::error!("Timer creation failed: {}", error);
+            return Ok(error.to_error_code());
+        }
+
+        if playerid < 0 {
+            let error = TimerError::ParameterValidation(format!("Invalid playerid: {}", playerid));
+            tracing::error!("Timer creation failed: {}", error);
+            return Ok(error.to_error_code());
+        }
+
+        let callback_data = match build_callback_data_from_format(&format, &mut args, PLAYER_FIXED_ARGS) {
+            Ok(data) if data.params.is_empty() => None,
+            Ok(data) => Some(data),
+            Err(error) => {
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        let options = TimerOptions {
+            player: Some(playerid),
+            ..TimerOptions::default()
+        };
+
+        match self.timer_manager.create_timer_with_options(owner_amx, delay_ms, repeat, callback_str, callback_data, options) {
+            Ok(timer_id) => {
+                tracing::debug!("Created timer {} bound to player {}", timer_id, playerid);
+                Ok(timer_id)
+            }
+            Err(e) => {
+                if e.is_recoverable() {
+                    tracing::warn!("Recoverable timer creation error: {}", e);
+                } else {
+                    tracing::error!("Fatal timer creation error: {}", e);
+                }
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_SetAt", raw)]
+    pub fn timer_set_at(&mut self, amx: &Amx, mut args: Args) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
+
+        let (timestamp, callback_str, fire_if_late, format) = match (
+            args.next::<i32>(),
+            args.next::<AmxString>(),
+            args.next::<bool>(),
+            args.next::<AmxString>(),
+        ) {
+            (Some(timestamp), Some(callback), Some(fire_if_late), Some(format)) => {
+                (timestamp, callback.to_string(), fire_if_late, format.to_string())
+            }
+            _ => {
+                let error = TimerError::ParameterParseError("Expected timestamp, callback, fire_if_late and format".to_string());
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        let callback_data = match build_callback_data_from_format(&format, &mut args, FORMAT_FIXED_ARGS) {
+            Ok(data) if data.params.is_empty() => None,
+            Ok(data) => Some(data),
+            Err(error) => {
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        match self.timer_manager.create_timer_at(owner_amx, timestamp as i64, fire_if_late, callback_str, callback_data) {
+            Ok(timer_id) => {
+                tracing::debug!("Created timer {} firing at timestamp {}", timer_id, timestamp);
+                Ok(timer_id)
+            }
+            Err(e) => {
+                if e.is_recoverable() {
+                    tracing::warn!("Recoverable timer creation error: {}", e);
+                } else {
+                    tracing::error!("Fatal timer creation error: {}", e);
+                }
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_SetCron", raw)]
+    pub fn timer_set_cron(&mut self, amx: &Amx, mut args: Args) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
+
+        let (expression, callback_str, format) = match (
+            args.next::<AmxString>(),
+            args.next::<AmxString>(),
+            args.next::<AmxString>(),
+        ) {
+            (Some(expression), Some(callback), Some(format)) => {
+                (expression.to_string(), callback.to_string(), format.to_string())
+            }
+            _ => {
+                let error = TimerError::ParameterParseError("Expected expression, callback and format".to_string());
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        let callback_data = match build_callback_data_from_format(&format, &mut args, CRON_FIXED_ARGS) {
+            Ok(data) if data.params.is_empty() => None,
+            Ok(data) => Some(data),
+            Err(error) => {
+                tracing::error!("Timer creation failed: {}", error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        match self.timer_manager.create_cron_timer(owner_amx, &expression, callback_str, callback_data) {
+            Ok(timer_id) => {
+                tracing::debug!("Created cron timer {} for '{}'", timer_id, expression);
+                Ok(timer_id)
+            }
+            Err(e) => {
+                if e.is_recoverable() {
+                    tracing::warn!("Recoverable timer creation error: {}", e);
+                } else {
+                    tracing::error!("Fatal timer creation error: {}", e);
+                }
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_SetCronTimezone")]
+    pub fn timer_set_cron_timezone(&mut self, amx: &Amx, offset_minutes: i32) -> AmxResult<bool> {
+        match cron::CronTimezone::from_offset_minutes(offset_minutes) {
+            Ok(timezone) => {
+                self.timer_manager.set_cron_timezone(AmxManager::amx_id(amx), timezone);
+                tracing::debug!("Cron time zone of AMX {:#x} set to {:?}", AmxManager::amx_id(amx), timezone);
+                Ok(true)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to set cron time zone: {}", e.to_user_message());
+                Ok(false)
+            }
+        }
+    }
     #[native(name = "Timer_Kill")]
     pub fn timer_kill(&mut self, _amx: &Amx, timer_id: i32) -> AmxResult<bool> {
         match self.timer_manager.kill_timer(timer_id) {
@@ -249,6 +747,148 @@ impl TimerPlugin {
             }
         }
     }
+    #[native(name = "Timer_Pause")]
+    pub fn timer_pause(&mut self, amx: &Amx, timer_id: i32) -> AmxResult<bool> {
+        match self.timer_manager.pause_timer(Some(AmxManager::amx_id(amx)), timer_id) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                tracing::warn!("Failed to pause timer {}: {}", timer_id, e.to_user_message());
+                Ok(false)
+            }
+        }
+    }
+    #[native(name = "Timer_Resume")]
+    pub fn timer_resume(&mut self, amx: &Amx, timer_id: i32) -> AmxResult<bool> {
+        match self.timer_manager.resume_timer(Some(AmxManager::amx_id(amx)), timer_id) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                tracing::warn!("Failed to resume timer {}: {}", timer_id, e.to_user_message());
+                Ok(false)
+            }
+        }
+    }
+    #[native(name = "Timer_IsPaused")]
+    pub fn timer_is_paused(&self, _amx: &Amx, timer_id: i32) -> AmxResult<bool> {
+        Ok(self.timer_manager.is_paused(timer_id).unwrap_or(false))
+    }
+    #[native(name = "Timer_SetInterval")]
+    pub fn timer_set_interval(&mut self, amx: &Amx, timer_id: i32, delay_ms: i32) -> AmxResult<i32> {
+        match self.timer_manager.set_interval(Some(AmxManager::amx_id(amx)), timer_id, delay_ms) {
+            Ok(()) => Ok(1),
+            Err(e) => {
+                tracing::warn!("Failed to change interval of timer {}: {}", timer_id, e.to_user_message());
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_Restart")]
+    pub fn timer_restart(&mut self, amx: &Amx, timer_id: i32) -> AmxResult<bool> {
+        match self.timer_manager.restart_timer(Some(AmxManager::amx_id(amx)), timer_id) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                tracing::warn!("Failed to restart timer {}: {}", timer_id, e.to_user_message());
+                Ok(false)
+            }
+        }
+    }
+    #[native(name = "Timer_SetFixedRate")]
+    pub fn timer_set_fixed_rate(&mut self, amx: &Amx, timer_id: i32, fixed_rate: bool, missed_ticks: i32) -> AmxResult<i32> {
+        let schedule = if fixed_rate {
+            match MissedTickPolicy::from_i32(missed_ticks) {
+                Some(policy) => ScheduleMode::FixedRate(policy),
+                None => {
+                    let error = TimerError::ParameterValidation(format!("Invalid missed tick policy: {}", missed_ticks));
+                    tracing::warn!("Failed to change schedule of timer {}: {}", timer_id, error);
+                    return Ok(error.to_error_code());
+                }
+            }
+        } else {
+            ScheduleMode::FixedDelay
+        };
+
+        match self.timer_manager.set_schedule_mode(Some(AmxManager::amx_id(amx)), timer_id, schedule) {
+            Ok(()) => Ok(1),
+            Err(e) => {
+                tracing::warn!("Failed to change schedule of timer {}: {}", timer_id, e.to_user_message());
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_KillPlayerTimers")]
+    pub fn timer_kill_player_timers(&mut self, _amx: &Amx, playerid: i32) -> AmxResult<i32> {
+        let killed = self.timer_manager.kill_player_timers(playerid);
+        if killed > 0 {
+            tracing::debug!("Killed {} timers bound to player {}", killed, playerid);
+        }
+        Ok(killed as i32)
+    }
+    #[native(name = "Timer_SetGroup")]
+    pub fn timer_set_group(&mut self, amx: &Amx, timer_id: i32, group: i32) -> AmxResult<i32> {
+        let group = match group {
+            TIMER_NO_GROUP => None,
+            group if group >= 0 => Some(group),
+            _ => {
+                let error = TimerError::ParameterValidation(format!("Invalid group: {}", group));
+                tracing::warn!("Failed to set group of timer {}: {}", timer_id, error);
+                return Ok(error.to_error_code());
+            }
+        };
+
+        match self.timer_manager.set_group(AmxManager::amx_id(amx), timer_id, group) {
+            Ok(()) => Ok(1),
+            Err(e) => {
+                tracing::warn!("Failed to set group of timer {}: {}", timer_id, e.to_user_message());
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_KillGroup")]
+    pub fn timer_kill_group(&mut self, amx: &Amx, group: i32) -> AmxResult<i32> {
+        let killed = self.timer_manager.kill_group(AmxManager::amx_id(amx), group);
+        tracing::debug!("Killed {} timers in group {}", killed, group);
+        Ok(killed as i32)
+    }
+    #[native(name = "Timer_PauseGroup")]
+    pub fn timer_pause_group(&mut self, amx: &Amx, group: i32) -> AmxResult<i32> {
+        Ok(self.timer_manager.pause_group(AmxManager::amx_id(amx), group) as i32)
+    }
+    #[native(name = "Timer_ResumeGroup")]
+    pub fn timer_resume_group(&mut self, amx: &Amx, group: i32) -> AmxResult<i32> {
+        Ok(self.timer_manager.resume_group(AmxManager::amx_id(amx), group) as i32)
+    }
+    #[native(name = "Timer_CountGroup")]
+    pub fn timer_count_group(&self, amx: &Amx, group: i32) -> AmxResult<i32> {
+        Ok(self.timer_manager.count_group(AmxManager::amx_id(amx), group) as i32)
+    }
+    #[native(name = "Timer_GetQuota")]
+    pub fn timer_get_quota(&self, _amx: &Amx, kind: i32) -> AmxResult<i32> {
+        match QuotaKind::from_i32(kind) {
+            Some(kind) => Ok(self.timer_manager.quota(kind).min(i32::MAX as usize) as i32),
+            None => Ok(TimerError::ParameterValidation(format!("Invalid quota: {}", kind)).to_error_code()),
+        }
+    }
+    #[native(name = "Timer_GetScriptTimerCount")]
+    pub fn timer_get_script_timer_count(&self, amx: &Amx) -> AmxResult<i32> {
+        Ok(self.timer_manager.script_timer_count(AmxManager::amx_id(amx)) as i32)
+    }
+    #[native(name = "Timer_GetRemaining")]
+    pub fn timer_get_remaining(&self, _amx: &Amx, timer_id: i32) -> AmxResult<i32> {
+        match self.timer_manager.get_remaining(timer_id) {
+            Ok(remaining) => Ok(duration_to_ms(remaining)),
+            Err(e) => {
+                tracing::debug!("Failed to get remaining time of timer {}: {}", timer_id, e.to_user_message());
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_GetRemainingCount")]
+    pub fn timer_get_remaining_count(&self, _amx: &Amx, timer_id: i32) -> AmxResult<i32> {
+        match self.timer_manager.get_executions_left(timer_id) {
+            Ok(Some(left)) => Ok(left.min(TIMER_COUNT_UNLIMITED as u64 - 1) as i32),
+            Ok(None) => Ok(TIMER_COUNT_UNLIMITED),
+            Err(e) => Ok(e.to_error_code()),
+        }
+    }
     #[native(name = "Timer_GetActiveCount")]
     pub fn timer_get_active_count(&self, _amx: &Amx) -> AmxResult<i32> {
         let count = self.timer_manager.active_timer_count();
@@ -265,10 +905,10 @@ impl TimerPlugin {
     #[native(name = "Timer_GetInfo")]
     pub fn timer_get_info(&self, _amx: &Amx, timer_id: i32) -> AmxResult<i32> {
         match self.timer_manager.get_timer_info(timer_id) {
-            Some((delay_ms, repeat, callback, elapsed)) => {
-                tracing::debug!("Timer {} info: delay={}ms, repeat={}, callback={}, elapsed={:?}",
-                               timer_id, delay_ms, repeat, callback, elapsed);
-                Ok(delay_ms as i32)
+            Some(info) => {
+                tracing::debug!("Timer {} info: delay={}ms, initial_delay={}ms, repeat={}, callback={}, elapsed={:?}",
+                               timer_id, info.delay_ms, info.initial_delay_ms, info.repeat, info.callback, info.elapsed);
+                Ok(info.delay_ms as i32)
             }
             None => {
                 tracing::warn!("Timer {} not found", timer_id);
@@ -276,6 +916,204 @@ impl TimerPlugin {
             }
         }
     }
+    #[native(name = "Timer_GetInfoEx")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn timer_get_info_ex(
+        &self,
+        _amx: &Amx,
+        timer_id: i32,
+        mut delay_ms: Ref<i32>,
+        mut repeat: Ref<bool>,
+        callback: UnsizedBuffer,
+        callback_size: i32,
+        mut execution_count: Ref<i32>,
+        mut last_execution: Ref<i32>,
+        mut remaining: Ref<i32>,
+        mut owner: Ref<i32>,
+    ) -> AmxResult<bool> {
+        let info = match self.timer_manager.get_timer_info(timer_id) {
+            Some(info) => info,
+            None => {
+                tracing::warn!("Timer {} not found", timer_id);
+                return Ok(false);
+            }
+        };
+
+        *delay_ms = info.delay_ms as i32;
+        *repeat = info.repeat;
+        *execution_count = info.execution_count.min(i32::MAX as u64) as i32;
+        *last_execution = info.last_execution_age.map_or(-1, duration_to_ms);
+        *remaining = duration_to_ms(info.remaining);
+        *owner = info.owner_amx as i32;
+
+        Ok(put_string(callback, callback_size, &info.callback))
+    }
+    #[native(name = "Timer_GetDelay")]
+    pub fn timer_get_delay(&self, _amx: &Amx, timer_id: i32) -> AmxResult<i32> {
+        match self.timer_manager.get_timer_info(timer_id) {
+            Some(info) => Ok(info.delay_ms as i32),
+            None => Ok(TimerError::TimerNotFound(timer_id).to_error_code()),
+        }
+    }
+    #[native(name = "Timer_IsRepeating")]
+    pub fn timer_is_repeating(&self, _amx: &Amx, timer_id: i32) -> AmxResult<bool> {
+        Ok(self.timer_manager.get_timer_info(timer_id).is_some_and(|info| info.repeat))
+    }
+    #[native(name = "Timer_GetCallback")]
+    pub fn timer_get_callback(&self, _amx: &Amx, timer_id: i32, dest: UnsizedBuffer, size: i32) -> AmxResult<bool> {
+        match self.timer_manager.get_timer_info(timer_id) {
+            Some(info) => Ok(put_string(dest, size, &info.callback)),
+            None => Ok(false),
+        }
+    }
+    #[native(name = "Timer_GetExecutionCount")]
+    pub fn timer_get_execution_count(&self, _amx: &Amx, timer_id: i32) -> AmxResult<i32> {
+        match self.timer_manager.get_timer_info(timer_id) {
+            Some(info) => Ok(info.execution_count.min(i32::MAX as u64) as i32),
+            None => Ok(TimerError::TimerNotFound(timer_id).to_error_code()),
+        }
+    }
+    #[native(name = "Timer_GetLastExecution")]
+    pub fn timer_get_last_execution(&self, _amx: &Amx, timer_id: i32) -> AmxResult<i32> {
+        match self.timer_manager.get_timer_info(timer_id) {
+            Some(info) => Ok(info.last_execution_age.map_or(-1, duration_to_ms)),
+            None => Ok(TimerError::TimerNotFound(timer_id).to_error_code()),
+        }
+    }
+    #[native(name = "Timer_GetOwner")]
+    pub fn timer_get_owner(&self, _amx: &Amx, timer_id: i32) -> AmxResult<i32> {
+        match self.timer_manager.get_timer_info(timer_id) {
+            Some(info) => Ok(info.owner_amx as i32),
+            None => Ok(0),
+        }
+    }
+    #[native(name = "Timer_GetScriptId")]
+    pub fn timer_get_script_id(&self, amx: &Amx) -> AmxResult<i32> {
+        Ok(AmxManager::amx_id(amx) as i32)
+    }
+    #[native(name = "Timer_GetQueueDepth")]
+    pub fn timer_get_queue_depth(&self, _amx: &Amx) -> AmxResult<i32> {
+        let stats = self.timer_manager.dispatch_stats();
+        Ok(stats.queue_depth as i32)
+    }
+
+    #[native(name = "Timer_GetDispatchLatency")]
+    pub fn timer_get_dispatch_latency(&self, _amx: &Amx, peak: bool) -> AmxResult<i32> {
+        let stats = self.timer_manager.dispatch_stats();
+        let latency = if peak { stats.max_drain_latency } else { stats.last_drain_latency };
+        Ok(duration_to_us(latency))
+    }
+    #[native(name = "Timer_RegisterScript")]
+    pub fn timer_register_script(&mut self, amx: &Amx, name: AmxString) -> AmxResult<i32> {
+        let owner_amx = AmxManager::register_amx(amx);
+        match self.persistence.register_script(&self.timer_manager, owner_amx, &name.to_string()) {
+            Ok(restored) => Ok(restored as i32),
+            Err(e) => {
+                tracing::warn!("Failed to register script: {}", e.to_user_message());
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_SetPersistent")]
+    pub fn timer_set_persistent(&mut self, amx: &Amx, timer_id: i32, mode: i32) -> AmxResult<i32> {
+        let owner_amx = AmxManager::amx_id(amx);
+        let mode = match mode {
+            TIMER_PERSIST_NONE => None,
+            mode => match PersistMode::from_i32(mode) {
+                Some(mode) => Some(mode),
+                None => {
+                    let error = TimerError::ParameterValidation(format!("Invalid persist mode: {}", mode));
+                    return Ok(error.to_error_code());
+                }
+            },
+        };
+
+        match self.persistence.set_persistent(&self.timer_manager, owner_amx, timer_id, mode) {
+            Ok(()) => Ok(1),
+            Err(e) => {
+                tracing::warn!("Failed to set persistence of timer {}: {}", timer_id, e.to_user_message());
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_DumpToFile")]
+    pub fn timer_dump_to_file(&self, _amx: &Amx, path: AmxString) -> AmxResult<i32> {
+        let path = match dump::script_path(&path.to_string()) {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::error!("Failed to dump timers: {}", e);
+                return Ok(e.to_error_code());
+            }
+        };
+        match self.timer_manager.dump_to_file(&path) {
+            Ok(count) => {
+                tracing::info!("Dumped {} timers to {}", count, path.display());
+                Ok(count as i32)
+            }
+            Err(e) => {
+                tracing::error!("Failed to dump timers: {}", e);
+                Ok(e.to_error_code())
+            }
+        }
+    }
+    #[native(name = "Timer_RconCommand")]
+    pub fn timer_rcon_command(&mut self, _amx: &Amx, command: AmxString) -> AmxResult<bool> {
+        let command = command.to_string();
+        let parsed = match RconCommand::parse(&command) {
+            Some(parsed) => parsed,
+            None => return Ok(false),
+        };
+
+        tracing::info!("RCON command: {}", command.trim());
+        for line in rcon::execute(&self.timer_manager, &parsed) {
+            server_log::print(&line);
+        }
+        Ok(true)
+    }
+    #[native(name = "Timer_GetStats")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn timer_get_stats(
+        &self,
+        _amx: &Amx,
+        timer_id: i32,
+        mut successes: Ref<i32>,
+        mut failures: Ref<i32>,
+        mut min_us: Ref<i32>,
+        mut avg_us: Ref<i32>,
+        mut max_us: Ref<i32>,
+        mut p99_us: Ref<i32>,
+        last_error: UnsizedBuffer,
+        last_error_size: i32,
+    ) -> AmxResult<i32> {
+        let stats = match self.timer_manager.get_timer_stats(timer_id) {
+            Ok(stats) => stats,
+            Err(e) => return Ok(e.to_error_code()),
+        };
+
+        *successes = stats.successes.min(i32::MAX as u64) as i32;
+        *failures = stats.failures.min(i32::MAX as u64) as i32;
+        *min_us = duration_to_us(stats.min_duration);
+        *avg_us = duration_to_us(stats.avg_duration);
+        *max_us = duration_to_us(stats.max_duration);
+        *p99_us = duration_to_us(stats.p99_duration);
+        put_string(last_error, last_error_size, stats.last_error.as_deref().unwrap_or(""));
+
+        Ok(1)
+    }
+    #[native(name = "Timer_GetSlowest")]
+    pub fn timer_get_slowest(&self, _amx: &Amx, timer_ids: UnsizedBuffer, count: i32) -> AmxResult<i32> {
+        if count <= 0 {
+            return Ok(0);
+        }
+
+        let slowest = self.timer_manager.slowest_timers(count as usize);
+        let mut buffer = timer_ids.into_sized_buffer(count as usize);
+        for (slot, (timer_id, _)) in buffer.iter_mut().zip(&slowest) {
+            *slot = *timer_id;
+        }
+
+        Ok(slowest.len() as i32)
+    }
 
 }
 
@@ -285,19 +1123,83 @@ initialize_plugin!(
         TimerPlugin::timer_set_ex,
         TimerPlugin::timer_set_once,
         TimerPlugin::timer_set_once_ex,
+        TimerPlugin::timer_set_fmt,
+        TimerPlugin::timer_set_count,
+        TimerPlugin::timer_set_initial,
+        TimerPlugin::timer_set_for_player,
+        TimerPlugin::timer_set_at,
+        TimerPlugin::timer_set_cron,
+        TimerPlugin::timer_set_cron_timezone,
         TimerPlugin::timer_kill,
+        TimerPlugin::timer_pause,
+        TimerPlugin::timer_resume,
+        TimerPlugin::timer_is_paused,
+        TimerPlugin::timer_set_interval,
+        TimerPlugin::timer_restart,
+        TimerPlugin::timer_set_fixed_rate,
+        TimerPlugin::timer_kill_player_timers,
+        TimerPlugin::timer_set_group,
+        TimerPlugin::timer_kill_group,
+        TimerPlugin::timer_pause_group,
+        TimerPlugin::timer_resume_group,
+        TimerPlugin::timer_count_group,
+        TimerPlugin::timer_get_quota,
+        TimerPlugin::timer_get_script_timer_count,
+        TimerPlugin::timer_get_remaining,
+        TimerPlugin::timer_get_remaining_count,
         TimerPlugin::timer_get_active_count,
         TimerPlugin::timer_get_amx_instance_count,
         TimerPlugin::timer_get_info,
+        TimerPlugin::timer_get_info_ex,
+        TimerPlugin::timer_get_delay,
+        TimerPlugin::timer_is_repeating,
+        TimerPlugin::timer_get_callback,
+        TimerPlugin::timer_get_execution_count,
+        TimerPlugin::timer_get_last_execution,
+        TimerPlugin::timer_get_owner,
+        TimerPlugin::timer_get_script_id,
+        TimerPlugin::timer_get_queue_depth,
+        TimerPlugin::timer_get_dispatch_latency,
+        TimerPlugin::timer_register_script,
+        TimerPlugin::timer_set_persistent,
+        TimerPlugin::timer_dump_to_file,
+        TimerPlugin::timer_rcon_command,
+        TimerPlugin::timer_get_stats,
+        TimerPlugin::timer_get_slowest,
     ],
     {
-        tracing_subscriber::fmt()
-            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-            .init();
+        /* the log settings come from the config, so its errors are reported once logging is up */
+        let loaded_config = config::Config::load(std::path::Path::new(config::CONFIG_PATH));
+        let log_guard = match loaded_config {
+            Ok(ref loaded_config) => logging::init(loaded_config),
+            Err(_) => logging::init(&config::Config::default()),
+        };
+
+        /* takes over samp's default logger, which would forward nothing from tracing */
+        let (_, server_logger) = samp::plugin::logger().into_log();
+        server_log::set_logger(server_logger);
+
+        match loaded_config {
+            Ok(loaded_config) => config::init(loaded_config),
+            Err(e) => tracing::error!("Failed to load {}, using defaults: {}", config::CONFIG_PATH, e),
+        }
+
+        /* callbacks are only ever executed from process_tick */
+        samp::plugin::enable_process_tick();
 
         let timer_manager = TimerManager::new().expect("Failed to initialize timer manager");
+
+        let dump_file = &config::get().dump.file;
+        if !dump_file.is_empty() {
+            timer_manager.dump_on_panic(dump_file.into());
+        }
+
+        let persistence = PersistentStore::load(&config::get().persistence);
+
         TimerPlugin {
             timer_manager,
+            persistence,
+            log_guard,
         }
     }
 );
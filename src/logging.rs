@@ -0,0 +1,202 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry};
+
+use crate::config::{Config, LogConfig, LogFormat, LogRotation};
+use crate::server_log::ServerLogLayer;
+use crate::error::{TimerError, TimerResult};
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/*
+ * Installs the global subscriber: stdout filtered by RUST_LOG as before, the log file
+ * filtered by log.level and server_log.txt filtered by server_log.level.
+ * The returned guard flushes the file when dropped.
+ */
+pub fn init(config: &Config) -> Option<WorkerGuard> {
+    let (file_layer, guard, file_error) = match file_layer(&config.log) {
+        Ok(Some((layer, guard))) => (Some(layer), Some(guard), None),
+        Ok(None) => (None, None, None),
+        Err(e) => (None, None, Some(e)),
+    };
+
+    let stdout_layer = tracing_subscriber::fmt::layer()
+        .with_filter(EnvFilter::from_default_env());
+
+    let server_log_level: LevelFilter = config.server_log.level.parse().unwrap_or(LevelFilter::WARN);
+    let server_log_layer = ServerLogLayer::new(Duration::from_secs(config.server_log.rate_limit_secs))
+        .with_filter(server_log_level);
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(server_log_layer)
+        .with(stdout_layer)
+        .init();
+
+    if let Some(e) = file_error {
+        tracing::error!("Logging to {} disabled: {}", config.log.file, e);
+    }
+
+    guard
+}
+
+fn file_layer(config: &LogConfig) -> TimerResult<Option<(BoxedLayer, WorkerGuard)>> {
+    if config.file.is_empty() {
+        return Ok(None);
+    }
+
+    let path = Path::new(&config.file);
+    let directory = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let prefix = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("timers");
+
+    let rotation = match config.rotation {
+        LogRotation::Never => Rotation::NEVER,
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Weekly => Rotation::WEEKLY,
+        LogRotation::Size => {
+            let file = SizeRollingFile::open(path, config.max_size_mb * 1024 * 1024, config.max_files)
+                .map_err(|e| TimerError::InvalidConfig(format!("Failed to open log file: {}", e)))?;
+            return Ok(Some(format_layer(config, tracing_appender::non_blocking(file))));
+        }
+    };
+
+    /* rotated files are named e.g. timers.2024-01-31.log */
+    let mut builder = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(prefix);
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        builder = builder.filename_suffix(extension);
+    }
+    if config.max_files > 0 {
+        builder = builder.max_log_files(config.max_files);
+    }
+
+    let appender = builder
+        .build(directory)
+        .map_err(|e| TimerError::InvalidConfig(format!("Failed to open log file: {}", e)))?;
+
+    Ok(Some(format_layer(config, tracing_appender::non_blocking(appender))))
+}
+
+fn format_layer(config: &LogConfig, (writer, guard): (NonBlocking, WorkerGuard)) -> (BoxedLayer, WorkerGuard) {
+    let level: LevelFilter = config.level.parse().unwrap_or(LevelFilter::INFO);
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(false);
+
+    let layer: BoxedLayer = match config.format {
+        LogFormat::Plain => layer.with_filter(level).boxed(),
+        LogFormat::Json => layer.json().with_filter(level).boxed(),
+    };
+
+    (layer, guard)
+}
+
+/* keeps its name and moves the full file to timers.1.log, shifting older ones to timers.2.log and so on */
+struct SizeRollingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl SizeRollingFile {
+    fn open(path: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        if let Some(directory) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(directory)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+
+        Ok(SizeRollingFile {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let stem = self.path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("timers");
+        let name = match self.path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) => format!("{}.{}.{}", stem, index, extension),
+            None => format!("{}.{}", stem, index),
+        };
+        self.path.with_file_name(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        /* 0 keeps every file, so the oldest one is shifted instead of dropped */
+        let keep = match self.max_files {
+            0 => (1..).take_while(|&index| self.rotated_path(index).exists()).count() + 1,
+            max_files => max_files,
+        };
+
+        let _ = fs::remove_file(self.rotated_path(keep));
+        for index in (1..keep).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        /* a single line larger than max_size still goes into one file */
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_rotation() {
+        let directory = std::env::temp_dir().join(format!("samp-timers-log-{}", std::process::id()));
+        let path = directory.join("timers.log");
+        let _ = fs::remove_dir_all(&directory);
+
+        let mut file = SizeRollingFile::open(&path, 100, 2).expect("Failed to open log file");
+        for line in 0..5 {
+            file.write_all(format!("{:<59}\n", line).as_bytes()).expect("Failed to write log line");
+        }
+
+        /* one 60 byte line per file, only the two newest rotated files are kept */
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap_or_default();
+        assert!(read(path.clone()).starts_with('4'));
+        assert!(read(directory.join("timers.1.log")).starts_with('3'));
+        assert!(read(directory.join("timers.2.log")).starts_with('2'));
+        assert!(!directory.join("timers.3.log").exists());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use dashmap::DashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::error::TimerError;
+
+/* upper bounds in seconds, chosen around the 10ms slow callback warning */
+const LATENCY_BUCKETS: [f64; 11] = [0.0001, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0];
+
+#[derive(Default)]
+struct ScriptCounters {
+    created: AtomicU64,
+    killed: AtomicU64,
+    completed: AtomicU64,
+}
+
+type CounterField = fn(&ScriptCounters) -> &AtomicU64;
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|&bound| seconds <= bound) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+lazy_static::lazy_static! {
+    /* by AMX, dropped when the script unloads so reloads don't pile up series */
+    static ref SCRIPT_COUNTERS: DashMap<usize, ScriptCounters> = DashMap::new();
+    /* names given with Timer_RegisterScript, stable across reloads unlike the AMX id */
+    static ref SCRIPT_NAMES: DashMap<usize, String> = DashMap::new();
+    static ref FAILURES: DashMap<&'static str, AtomicU64> = DashMap::new();
+    static ref CALLBACK_DURATION: Histogram = Histogram::default();
+}
+
+pub fn timer_created(owner_amx: usize) {
+    SCRIPT_COUNTERS.entry(owner_amx).or_default().created.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn timer_killed(owner_amx: usize) {
+    SCRIPT_COUNTERS.entry(owner_amx).or_default().killed.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn timer_completed(owner_amx: usize) {
+    SCRIPT_COUNTERS.entry(owner_amx).or_default().completed.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn script_registered(owner_amx: usize, name: &str) {
+    SCRIPT_NAMES.insert(owner_amx, name.to_string());
+}
+
+pub fn script_unloaded(owner_amx: usize) {
+    SCRIPT_COUNTERS.remove(&owner_amx);
+    SCRIPT_NAMES.remove(&owner_amx);
+}
+
+/* the registered name, or the AMX id for scripts that never registered */
+fn script_label(owner_amx: usize) -> String {
+    SCRIPT_NAMES.get(&owner_amx).map_or_else(|| format!("{:#x}", owner_amx), |name| name.clone())
+}
+
+/* label values are quoted, see the text exposition format */
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/* failed callbacks included */
+pub fn callback_executed(duration: Duration) {
+    CALLBACK_DURATION.observe(duration);
+}
+
+pub fn failure(error: &TimerError) {
+    FAILURES.entry(error_label(error)).or_default().fetch_add(1, Ordering::Relaxed);
+}
+
+fn error_label(error: &TimerError) -> &'static str {
+    match error {
+        TimerError::InvalidDelay(_) => "invalid_delay",
+        TimerError::TimerNotFound(_) => "timer_not_found",
+        TimerError::InvalidCallback(_) => "invalid_callback",
+        TimerError::ParameterParseError(_) => "parameter_parse",
+        TimerError::SystemShutdown => "system_shutdown",
+        TimerError::TaskSpawnError(_) => "task_spawn",
+        TimerError::CallbackExecutionError(_) => "callback_execution",
+        TimerError::IdOverflow => "id_overflow",
+        TimerError::ResourceExhaustion(_) => "resource_exhaustion",
+        TimerError::ParameterValidation(_) => "parameter_validation",
+        TimerError::TimestampInPast(_) => "timestamp_in_past",
+        TimerError::ScriptQuotaExceeded(_) => "script_quota",
+        TimerError::GroupQuotaExceeded(_) => "group_quota",
+        TimerError::InvalidConfig(_) => "invalid_config",
+        TimerError::FileError(_) => "file",
+        TimerError::NotOwner(_) => "not_owner",
+        TimerError::Internal(_) => "internal",
+    }
+}
+
+/* Prometheus text exposition format */
+pub fn render(active_timers: usize) -> String {
+    let mut out = String::new();
+
+    let counters: [(&str, &str, CounterField); 3] = [
+        ("samp_timers_created_total", "Timers created", |c| &c.created),
+        ("samp_timers_killed_total", "Timers killed before finishing", |c| &c.killed),
+        ("samp_timers_completed_total", "Timers that ran to completion", |c| &c.completed),
+    ];
+    for (name, help, counter) in counters {
+        let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+        for entry in SCRIPT_COUNTERS.iter() {
+            let script = escape_label(&script_label(*entry.key()));
+            let _ = writeln!(out, "{}{{script=\"{}\"}} {}", name, script, counter(entry.value()).load(Ordering::Relaxed));
+        }
+    }
+
+    let _ = writeln!(out, "# HELP samp_timers_failures_total Timer failures by error\n# TYPE samp_timers_failures_total counter");
+    for entry in FAILURES.iter() {
+        let _ = writeln!(out, "samp_timers_failures_total{{error=\"{}\"}} {}", entry.key(), entry.value().load(Ordering::Relaxed));
+    }
+
+    let _ = writeln!(out, "# HELP samp_timers_callback_duration_seconds Callback execution time\n# TYPE samp_timers_callback_duration_seconds histogram");
+    let mut cumulative = 0;
+    for (bound, bucket) in LATENCY_BUCKETS.iter().zip(CALLBACK_DURATION.buckets.iter()) {
+        cumulative += bucket.load(Ordering::Relaxed);
+        let _ = writeln!(out, "samp_timers_callback_duration_seconds_bucket{{le=\"{}\"}} {}", bound, cumulative);
+    }
+    let count = CALLBACK_DURATION.count.load(Ordering::Relaxed);
+    let _ = writeln!(out, "samp_timers_callback_duration_seconds_bucket{{le=\"+Inf\"}} {}", count);
+    let _ = writeln!(out, "samp_timers_callback_duration_seconds_sum {}", CALLBACK_DURATION.sum_us.load(Ordering::Relaxed) as f64 / 1_000_000.0);
+    let _ = writeln!(out, "samp_timers_callback_duration_seconds_count {}", count);
+
+    let _ = writeln!(out, "# HELP samp_timers_active Timers currently active\n# TYPE samp_timers_active gauge\nsamp_timers_active {}", active_timers);
+
+    out
+}
+
+/* minimal HTTP/1.1 server answering GET /metrics, one request per connection */
+pub async fn serve<F>(listener: TcpListener, active_timers: F)
+where
+    F: Fn() -> usize + Send + Sync + 'static,
+{
+    let active_timers = std::sync::Arc::new(active_timers);
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                tracing::warn!("Metrics listener failed to accept a connection: {}", e);
+                continue;
+            }
+        };
+
+        let active_timers = std::sync::Arc::clone(&active_timers);
+        tokio::spawn(async move {
+            if let Err(e) = respond(stream, active_timers()).await {
+                tracing::debug!("Metrics request failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(mut stream: TcpStream, active_timers: usize) -> std::io::Result<()> {
+    let mut request = [0u8; 1024];
+    let read = stream.read(&mut request).await?;
+    let request_line = String::from_utf8_lossy(&request[..read]);
+
+    let (status, body) = if request_line.starts_with("GET /metrics ") {
+        ("200 OK", render(active_timers))
+    } else {
+        ("404 Not Found", "Not Found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        timer_created(0x1000);
+        timer_killed(0x1000);
+        timer_created(0x2000);
+        script_registered(0x2000, "game\"mode");
+        failure(&TimerError::CallbackExecutionError("boom".to_string()));
+        callback_executed(Duration::from_millis(3));
+
+        let output = render(7);
+        assert!(output.contains("samp_timers_created_total{script=\"0x1000\"} 1"));
+        assert!(output.contains("samp_timers_killed_total{script=\"0x1000\"} 1"));
+        assert!(output.contains("samp_timers_created_total{script=\"game\\\"mode\"} 1"));
+        assert!(output.contains("samp_timers_failures_total{error=\"callback_execution\"}"));
+        assert!(output.contains("samp_timers_callback_duration_seconds_bucket{le=\"0.005\"}"));
+        assert!(output.contains("samp_timers_active 7"));
+
+        script_unloaded(0x2000);
+        assert!(!render(7).contains("game"));
+    }
+}
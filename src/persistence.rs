@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chrono::{SecondsFormat, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::callback::CallbackData;
+use crate::config::PersistenceConfig;
+use crate::dump;
+use crate::error::{TimerError, TimerResult};
+use crate::timer::{MissedTickPolicy, PersistMode, ScheduleMode, Timer, TimerManager, TimerOptions};
+
+const FILE_VERSION: u32 = 1;
+
+/* cron timers are not persisted, scripts set them up again on init */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PersistedSchedule {
+    FixedDelay,
+    FixedRate(MissedTickPolicy),
+}
+
+/* everything needed to re-arm a persistent timer for the script that registered under `script` */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedTimer {
+    pub script: String,
+    pub callback: String,
+    pub params: Option<CallbackData>,
+    pub delay_ms: u64,
+    pub repeat: bool,
+    pub schedule: PersistedSchedule,
+    pub executions_left: Option<u64>,
+    pub group: Option<i32>,
+    pub mode: PersistMode,
+    pub paused: bool,
+    pub remaining_ms: u64,
+    /* unix time in milliseconds the timer was due when saved */
+    pub deadline: i64,
+}
+
+impl PersistedTimer {
+    /* None for timers that are not persistent */
+    fn new(timer: &Timer, script: &str, now_ms: i64) -> Option<Self> {
+        let mode = timer.persist?;
+        let schedule = match timer.schedule {
+            ScheduleMode::FixedDelay => PersistedSchedule::FixedDelay,
+            ScheduleMode::FixedRate(policy) => PersistedSchedule::FixedRate(policy),
+            ScheduleMode::Cron(_) => return None,
+        };
+        let remaining_ms = timer.remaining().as_millis().min(i64::MAX as u128) as u64;
+
+        Some(PersistedTimer {
+            script: script.to_string(),
+            callback: timer.callback.clone(),
+            params: timer.params.clone(),
+            delay_ms: timer.delay_ms,
+            repeat: timer.repeat,
+            schedule,
+            executions_left: timer.executions_left,
+            group: timer.group,
+            mode,
+            paused: timer.is_paused(),
+            remaining_ms,
+            deadline: now_ms.saturating_add(remaining_ms as i64),
+        })
+    }
+
+    /* paused timers keep their remaining time whatever the mode */
+    fn initial_delay_ms(&self, now_ms: i64) -> u64 {
+        match self.mode {
+            PersistMode::Deadline if !self.paused => self.deadline.saturating_sub(now_ms).max(0) as u64,
+            _ => self.remaining_ms,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedFile {
+    version: u32,
+    saved_at: String,
+    timers: Vec<PersistedTimer>,
+}
+
+fn to_json(timers: &[PersistedTimer]) -> TimerResult<String> {
+    let file = PersistedFile {
+        version: FILE_VERSION,
+        saved_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        timers: timers.to_vec(),
+    };
+
+    serde_json::to_string_pretty(&file)
+        .map_err(|e| TimerError::Internal(format!("Failed to serialize persistent timers: {}", e)))
+}
+
+fn from_json(contents: &str) -> TimerResult<Vec<PersistedTimer>> {
+    let file: PersistedFile = serde_json::from_str(contents)
+        .map_err(|e| TimerError::FileError(format!("Invalid persistent timers: {}", e)))?;
+
+    if file.version != FILE_VERSION {
+        return Err(TimerError::FileError(format!("Unsupported persistent timers version {}", file.version)));
+    }
+
+    Ok(file.timers)
+}
+
+/* a missing file means nothing was saved yet */
+fn read_file(path: &Path) -> TimerResult<Vec<PersistedTimer>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => from_json(&contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(TimerError::FileError(format!("{}: {}", path.display(), e))),
+    }
+}
+
+/*
+ * Persistent timers outlive their script: they are captured when the script unloads,
+ * saved to the state file and re-armed when a script registers under the same name.
+ */
+pub struct PersistentStore {
+    path: Option<PathBuf>,
+    save_interval: Duration,
+    /* names given with Timer_RegisterScript, by AMX */
+    scripts: DashMap<usize, String>,
+    /* saved timers waiting for their script to register */
+    pending: Mutex<HashMap<String, Vec<PersistedTimer>>>,
+    last_save: Mutex<Instant>,
+    /* serializes writes from the periodic save and the server thread */
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl PersistentStore {
+    pub fn load(config: &PersistenceConfig) -> Self {
+        let path = (!config.file.is_empty()).then(|| PathBuf::from(&config.file));
+        let mut pending: HashMap<String, Vec<PersistedTimer>> = HashMap::new();
+
+        if let Some(ref path) = path {
+            match read_file(path) {
+                Ok(timers) => {
+                    if !timers.is_empty() {
+                        tracing::info!("Loaded {} persistent timers from {}", timers.len(), path.display());
+                    }
+                    for timer in timers {
+                        pending.entry(timer.script.clone()).or_default().push(timer);
+                    }
+                }
+                Err(e) => {
+                    /* keep the unreadable file around instead of overwriting it on the next save */
+                    let mut backup = path.as_os_str().to_owned();
+                    backup.push(".bad");
+                    let _ = std::fs::rename(path, &backup);
+                    tracing::error!("Failed to load persistent timers, moved to {}: {}", Path::new(&backup).display(), e);
+                }
+            }
+        }
+
+        PersistentStore {
+            path,
+            save_interval: Duration::from_secs(config.save_interval_secs),
+            scripts: DashMap::new(),
+            pending: Mutex::new(pending),
+            last_save: Mutex::new(Instant::now()),
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /* names the script and re-arms the timers saved under that name, returns how many */
+    pub fn register_script(&self, manager: &TimerManager, owner_amx: usize, name: &str) -> TimerResult<usize> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(TimerError::ParameterValidation("Script name must not be empty".to_string()));
+        }
+
+        if self.scripts.iter().any(|entry| *entry.key() != owner_amx && entry.value() == name) {
+            return Err(TimerError::ParameterValidation(
+                format!("Script name '{}' is already registered by another script", name)
+            ));
+        }
+        self.scripts.insert(owner_amx, name.to_string());
+        #[cfg(feature = "metrics")]
+        crate::metrics::script_registered(owner_amx, name);
+
+        let saved = self.pending.lock().remove(name).unwrap_or_default();
+        let now_ms = Utc::now().timestamp_millis();
+        let restored = saved
+            .into_iter()
+            .filter(|timer| match Self::restore(manager, owner_amx, timer, now_ms) {
+                Ok(timer_id) => {
+                    tracing::debug!("Restored persistent timer {} ({}) for script '{}'", timer_id, timer.callback, name);
+                    true
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to restore persistent timer {} for script '{}': {}", timer.callback, name, e);
+                    false
+                }
+            })
+            .count();
+
+        if restored > 0 {
+            tracing::info!("Restored {} persistent timers for script '{}'", restored, name);
+        }
+        Ok(restored)
+    }
+
+    fn restore(manager: &TimerManager, owner_amx: usize, timer: &PersistedTimer, now_ms: i64) -> TimerResult<i32> {
+        let delay_ms = i32::try_from(timer.delay_ms).map_err(|_| TimerError::InvalidDelay(i32::MAX))?;
+        let options = TimerOptions {
+            max_executions: timer.executions_left,
+            initial_delay_ms: Some(timer.initial_delay_ms(now_ms)),
+            schedule: match timer.schedule {
+                PersistedSchedule::FixedDelay => ScheduleMode::FixedDelay,
+                PersistedSchedule::FixedRate(policy) => ScheduleMode::FixedRate(policy),
+            },
+            player: None,
+            group: timer.group,
+            persist: Some(timer.mode),
+            paused: timer.paused,
+        };
+
+        /* fully set up before its task starts, nothing to undo if it fails */
+        manager.create_timer_with_options(
+            owner_amx,
+            delay_ms,
+            timer.repeat,
+            timer.callback.clone(),
+            timer.params.clone(),
+            options,
+        )
+    }
+
+    pub fn set_persistent(&self, manager: &TimerManager, owner_amx: usize, timer_id: i32, mode: Option<PersistMode>) -> TimerResult<()> {
+        if mode.is_some() {
+            if self.path.is_none() {
+                return Err(TimerError::ParameterValidation("Persistent timers are disabled in timers.toml".to_string()));
+            }
+
+            if !self.scripts.contains_key(&owner_amx) {
+                return Err(TimerError::ParameterValidation(
+                    "The timer's script must call Timer_RegisterScript first".to_string()
+                ));
+            }
+        }
+
+        manager.set_persistent(owner_amx, timer_id, mode)
+    }
+
+    /* keeps the persistent timers of an unloading script until it registers again */
+    pub fn unregister_script(&self, manager: &TimerManager, owner_amx: usize) -> usize {
+        let Some((_, name)) = self.scripts.remove(&owner_amx) else {
+            return 0;
+        };
+
+        let now_ms = Utc::now().timestamp_millis();
+        let captured = manager.filter_map_timers(|timer| {
+            (timer.owner_amx == owner_amx).then(|| PersistedTimer::new(timer, &name, now_ms)).flatten()
+        });
+
+        let count = captured.len();
+        if count > 0 {
+            self.pending.lock().entry(name.clone()).or_default().extend(captured);
+            tracing::info!("Kept {} persistent timers of script '{}'", count, name);
+        }
+        count
+    }
+
+    /* pending timers plus the persistent timers of registered scripts */
+    fn collect(&self, manager: &TimerManager) -> Vec<PersistedTimer> {
+        let now_ms = Utc::now().timestamp_millis();
+        let mut timers: Vec<PersistedTimer> = self.pending.lock().values().flatten().cloned().collect();
+
+        timers.extend(manager.filter_map_timers(|timer| {
+            let script = self.scripts.get(&timer.owner_amx)?;
+            PersistedTimer::new(timer, &script, now_ms)
+        }));
+        timers
+    }
+
+    pub fn save(&self, manager: &TimerManager) -> TimerResult<usize> {
+        let Some(ref path) = self.path else {
+            return Ok(0);
+        };
+
+        let timers = self.collect(manager);
+        let _write = self.write_lock.lock();
+        dump::write_atomic(path, &to_json(&timers)?)?;
+        *self.last_save.lock() = Instant::now();
+        Ok(timers.len())
+    }
+
+    /* called every server tick, writes the file off the server thread */
+    pub fn save_if_due(&self, manager: &TimerManager) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        if self.save_interval.is_zero() {
+            return;
+        }
+
+        {
+            let mut last_save = self.last_save.lock();
+            if last_save.elapsed() < self.save_interval {
+                return;
+            }
+            *last_save = Instant::now();
+        }
+
+        let timers = self.collect(manager);
+        let path = path.clone();
+        let write_lock = Arc::clone(&self.write_lock);
+
+        manager.spawn_blocking(move || {
+            let _write = write_lock.lock();
+            if let Err(e) = to_json(&timers).and_then(|json| dump::write_atomic(&path, &json)) {
+                tracing::error!("Failed to save persistent timers: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callback::CallbackParam;
+
+    #[test]
+    fn test_persisted_timer_roundtrip() {
+        let mut params = CallbackData::new();
+        params.add_param(CallbackParam::Integer(3)).expect("Failed to add integer param");
+        params.add_param(CallbackParam::Float(1.5)).expect("Failed to add float param");
+
+        let mut timer = Timer::new(0x1234, 60_000, false, "OnJailRelease".to_string(), Some(params), TimerOptions::default())
+            .expect("Failed to create timer");
+        assert!(PersistedTimer::new(&timer, "gamemode", 0).is_none());
+
+        timer.persist = Some(PersistMode::Deadline);
+        timer.schedule = ScheduleMode::FixedRate(MissedTickPolicy::Skip);
+        let saved = PersistedTimer::new(&timer, "gamemode", 1_000_000).expect("Timer should be persisted");
+
+        let restored = from_json(&to_json(&[saved]).expect("Failed to serialize")).expect("Failed to parse");
+        let restored = &restored[0];
+        assert_eq!(restored.script, "gamemode");
+        assert_eq!(restored.schedule, PersistedSchedule::FixedRate(MissedTickPolicy::Skip));
+        assert!(matches!(restored.params.as_ref().map(|p| &p.params[..]), Some([CallbackParam::Integer(3), CallbackParam::Float(_)])));
+
+        /* a deadline timer keeps counting while the server is down, a remaining one does not */
+        assert!(restored.initial_delay_ms(1_000_000) <= 60_000);
+        assert_eq!(restored.initial_delay_ms(1_000_000 + 120_000), 0);
+        let remaining = PersistedTimer { mode: PersistMode::Remaining, ..restored.clone() };
+        assert_eq!(remaining.initial_delay_ms(1_000_000 + 120_000), remaining.remaining_ms);
+
+        assert!(matches!(from_json("{\"version\": 2, \"saved_at\": \"\", \"timers\": []}"), Err(TimerError::FileError(_))));
+    }
+
+    /* never written to, the tests don't save */
+    fn test_store() -> PersistentStore {
+        PersistentStore::load(&PersistenceConfig {
+            file: std::env::temp_dir().join("samp-timers-test-missing.json").display().to_string(),
+            save_interval_secs: 0,
+        })
+    }
+
+    #[test]
+    fn test_restore_after_reload() {
+        let manager = TimerManager::for_tests();
+        let store = test_store();
+
+        let mut params = CallbackData::new();
+        params.add_param(CallbackParam::Integer(12)).expect("Failed to add integer param");
+        params.add_param(CallbackParam::String("spam".to_string())).expect("Failed to add string param");
+
+        store.register_script(manager, 0x30, "gamemode").expect("Failed to register script");
+        store.register_script(manager, 0x32, "filterscript").expect("Failed to register script");
+        assert!(matches!(store.register_script(manager, 0x33, "filterscript"), Err(TimerError::ParameterValidation(_))));
+
+        let timer_id = manager.create_timer(0x30, 60_000, false, "OnJailRelease".to_string(), Some(params))
+            .expect("Failed to create timer");
+        manager.set_group(0x30, timer_id, Some(4)).expect("Failed to group timer");
+        assert!(matches!(store.set_persistent(manager, 0x32, timer_id, Some(PersistMode::Remaining)), Err(TimerError::NotOwner(_))));
+        store.set_persistent(manager, 0x30, timer_id, Some(PersistMode::Remaining)).expect("Failed to persist timer");
+
+        /* a paused timer that was about to fire must stay paused */
+        let paused_id = manager.create_timer(0x30, 1, true, "OnPausedTick".to_string(), None)
+            .expect("Failed to create timer");
+        manager.pause_timer(Some(0x30), paused_id).expect("Failed to pause timer");
+        store.set_persistent(manager, 0x30, paused_id, Some(PersistMode::Remaining)).expect("Failed to persist timer");
+
+        /* unload: the timers are kept as pending and killed with the script */
+        assert_eq!(store.unregister_script(manager, 0x30), 2);
+        assert_eq!(manager.kill_timers_for_amx(0x30), 2);
+
+        assert_eq!(store.register_script(manager, 0x34, "gamemode").expect("Failed to register script"), 2);
+        let restored = manager.filter_map_timers(|timer| {
+            (timer.owner_amx == 0x34).then(|| (timer.callback.clone(), timer.params.clone(), timer.group, timer.persist, timer.is_paused()))
+        });
+        let (_, params, group, persist, paused) = restored.iter().find(|timer| timer.0 == "OnJailRelease").expect("Timer not restored");
+        assert!(matches!(params.as_ref().map(|p| &p.params[..]), Some([CallbackParam::Integer(12), CallbackParam::String(s)]) if s == "spam"));
+        assert_eq!((*group, *persist, *paused), (Some(4), Some(PersistMode::Remaining), false));
+        assert_eq!(manager.count_group(0x34, 4), 1);
+
+        std::thread::sleep(Duration::from_millis(50));
+        let paused = manager.filter_map_timers(|timer| {
+            (timer.owner_amx == 0x34 && timer.callback == "OnPausedTick").then(|| (timer.is_paused(), timer.execution_count))
+        });
+        assert_eq!(paused, [(true, 0)]);
+    }
+}
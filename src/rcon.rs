@@ -0,0 +1,310 @@
+use std::time::Duration;
+
+use crate::config;
+use crate::timer::{TimerInfo, TimerManager};
+
+/* rows printed by "timers list" before the rest is only counted */
+const MAX_LISTED_TIMERS: usize = 50;
+const SLOWEST_TIMERS: usize = 5;
+
+const USAGE: &str = "Usage: timers list [amx <id>|group <group>|callback <name>] | info <id> | kill <id> | pause <id> | resume <id> | stats";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListFilter {
+    All,
+    Amx(usize),
+    Group(i32),
+    Callback(String),
+}
+
+impl ListFilter {
+    fn matches(&self, info: &TimerInfo) -> bool {
+        match self {
+            ListFilter::All => true,
+            ListFilter::Amx(owner_amx) => info.owner_amx == *owner_amx,
+            ListFilter::Group(group) => info.group == Some(*group),
+            ListFilter::Callback(callback) => info.callback.eq_ignore_ascii_case(callback),
+        }
+    }
+}
+
+/* "timers ..." commands typed into the server console or sent over RCON */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RconCommand {
+    List(ListFilter),
+    Info(i32),
+    Kill(i32),
+    Pause(i32),
+    Resume(i32),
+    Stats,
+    Usage,
+}
+
+impl RconCommand {
+    /* None if the command is not meant for this plugin */
+    pub fn parse(command: &str) -> Option<Self> {
+        let mut words = command.split_whitespace();
+        if !words.next()?.eq_ignore_ascii_case("timers") {
+            return None;
+        }
+
+        let words: Vec<&str> = words.collect();
+        let timer_command = |id: &str, command: fn(i32) -> RconCommand| {
+            id.parse().map_or(RconCommand::Usage, command)
+        };
+
+        let command = match words.as_slice() {
+            ["list"] => RconCommand::List(ListFilter::All),
+            ["list", "amx", id] => parse_amx_id(id).map_or(RconCommand::Usage, |id| RconCommand::List(ListFilter::Amx(id))),
+            ["list", "group", group] => group.parse().map_or(RconCommand::Usage, |group| RconCommand::List(ListFilter::Group(group))),
+            ["list", "callback", callback] => RconCommand::List(ListFilter::Callback(callback.to_string())),
+            ["info", id] => timer_command(id, RconCommand::Info),
+            ["kill", id] => timer_command(id, RconCommand::Kill),
+            ["pause", id] => timer_command(id, RconCommand::Pause),
+            ["resume", id] => timer_command(id, RconCommand::Resume),
+            ["stats"] => RconCommand::Stats,
+            _ => RconCommand::Usage,
+        };
+
+        Some(command)
+    }
+}
+
+/* script IDs are printed in hex, Timer_GetScriptId returns them as a (possibly negative) cell */
+fn parse_amx_id(id: &str) -> Option<usize> {
+    match id.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => id.parse::<i32>().ok().map(|id| id as u32 as usize),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_millis(1) {
+        format!("{}us", duration.as_micros())
+    } else if duration < Duration::from_secs(10) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+/* output lines for the server console */
+pub fn execute(manager: &TimerManager, command: &RconCommand) -> Vec<String> {
+    let result = match command {
+        RconCommand::List(filter) => return list(manager, filter),
+        RconCommand::Info(timer_id) => return info(manager, *timer_id),
+        RconCommand::Stats => return stats(manager),
+        RconCommand::Usage => return vec![USAGE.to_string()],
+        RconCommand::Kill(timer_id) => manager.kill_timer(*timer_id).map(|_| format!("Timer {} killed", timer_id)),
+        RconCommand::Pause(timer_id) => manager.pause_timer(None, *timer_id).map(|_| format!("Timer {} paused", timer_id)),
+        RconCommand::Resume(timer_id) => manager.resume_timer(None, *timer_id).map(|_| format!("Timer {} resumed", timer_id)),
+    };
+
+    vec![result.unwrap_or_else(|e| e.to_user_message())]
+}
+
+fn list(manager: &TimerManager, filter: &ListFilter) -> Vec<String> {
+    let timers: Vec<(i32, TimerInfo)> = manager
+        .timer_ids()
+        .into_iter()
+        .filter_map(|timer_id| Some((timer_id, manager.get_timer_info(timer_id)?)))
+        .filter(|(_, info)| filter.matches(info))
+        .collect();
+
+    if timers.is_empty() {
+        return vec!["No matching timers".to_string()];
+    }
+
+    let mut lines = vec![format!(
+        "{:>7}  {:<24}  {:>10}  {:>8}  {:<6}  {:<7}  {:>9}  {:>8}  {:>5}",
+        "ID", "CALLBACK", "SCRIPT", "DELAY", "REPEAT", "STATE", "REMAINING", "EXECS", "GROUP"
+    )];
+
+    for (timer_id, info) in timers.iter().take(MAX_LISTED_TIMERS) {
+        lines.push(format!(
+            "{:>7}  {:<24}  {:>10}  {:>8}  {:<6}  {:<7}  {:>9}  {:>8}  {:>5}",
+            timer_id,
+            info.callback,
+            format!("{:#x}", info.owner_amx),
+            format_duration(Duration::from_millis(info.delay_ms)),
+            if info.repeat { "yes" } else { "no" },
+            if info.paused { "paused" } else { "running" },
+            format_duration(info.remaining),
+            info.execution_count,
+            info.group.map_or("-".to_string(), |group| group.to_string()),
+        ));
+    }
+
+    if timers.len() > MAX_LISTED_TIMERS {
+        lines.push(format!("... and {} more, narrow the list with a filter", timers.len() - MAX_LISTED_TIMERS));
+    } else {
+        lines.push(format!("{} timers", timers.len()));
+    }
+
+    lines
+}
+
+fn info(manager: &TimerManager, timer_id: i32) -> Vec<String> {
+    let (Some(info), Ok(stats)) = (manager.get_timer_info(timer_id), manager.get_timer_stats(timer_id)) else {
+        return vec![format!("Timer {} not found", timer_id)];
+    };
+
+    let mut lines = vec![
+        format!("Timer {}", timer_id),
+        format!("  callback:   {}", info.callback),
+        format!("  script:     {:#x}", info.owner_amx),
+        format!(
+            "  delay:      {}, {} (first run after {})",
+            format_duration(Duration::from_millis(info.delay_ms)),
+            if info.repeat { "repeating" } else { "once" },
+            format_duration(Duration::from_millis(info.initial_delay_ms))
+        ),
+        format!(
+            "  state:      {}, next run in {}, created {} ago",
+            if info.paused { "paused" } else { "running" },
+            format_duration(info.remaining),
+            format_duration(info.elapsed)
+        ),
+        format!(
+            "  executions: {} succeeded, {} failed, {}",
+            stats.successes,
+            stats.failures,
+            info.last_execution_age.map_or("not run yet".to_string(), |age| format!("last {} ago", format_duration(age)))
+        ),
+        format!(
+            "  duration:   min {}, avg {}, p99 {}, max {}",
+            format_duration(stats.min_duration),
+            format_duration(stats.avg_duration),
+            format_duration(stats.p99_duration),
+            format_duration(stats.max_duration)
+        ),
+    ];
+
+    if let Some(group) = info.group {
+        lines.push(format!("  group:      {}", group));
+    }
+    if let Some(player) = info.player {
+        lines.push(format!("  player:     {}", player));
+    }
+    if let Some(error) = stats.last_error {
+        lines.push(format!("  last error: {}", error));
+    }
+
+    lines
+}
+
+fn stats(manager: &TimerManager) -> Vec<String> {
+    let dispatch = manager.dispatch_stats();
+    let mut lines = vec![
+        format!("Active timers: {} (limit {})", manager.active_timer_count(), config::get().limits.max_timers),
+        format!(
+            "Queued callbacks: {}, dispatch latency {} (peak {})",
+            dispatch.queue_depth,
+            format_duration(dispatch.last_drain_latency),
+            format_duration(dispatch.max_drain_latency)
+        ),
+    ];
+
+    let slowest = manager.slowest_timers(SLOWEST_TIMERS);
+    if !slowest.is_empty() {
+        lines.push("Slowest timers by average callback duration:".to_string());
+    }
+    for (timer_id, stats) in slowest {
+        let callback = manager.get_timer_info(timer_id).map_or_else(String::new, |info| info.callback);
+        lines.push(format!(
+            "  {:>7}  {:<24}  avg {}, p99 {}, max {}, {} executions",
+            timer_id,
+            callback,
+            format_duration(stats.avg_duration),
+            format_duration(stats.p99_duration),
+            format_duration(stats.max_duration),
+            stats.successes
+        ));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TimerError;
+
+    #[test]
+    fn test_parse_rcon_command() {
+        assert_eq!(RconCommand::parse("gmx"), None);
+        assert_eq!(RconCommand::parse("timersfoo list"), None);
+        assert_eq!(RconCommand::parse("timers"), Some(RconCommand::Usage));
+        assert_eq!(RconCommand::parse("timers list"), Some(RconCommand::List(ListFilter::All)));
+        assert_eq!(RconCommand::parse("TIMERS  info  42"), Some(RconCommand::Info(42)));
+        assert_eq!(RconCommand::parse("timers kill abc"), Some(RconCommand::Usage));
+        assert_eq!(RconCommand::parse("timers list amx 0x1f40"), Some(RconCommand::List(ListFilter::Amx(0x1f40))));
+        assert_eq!(RconCommand::parse("timers list amx -1"), Some(RconCommand::List(ListFilter::Amx(u32::MAX as usize))));
+        assert_eq!(RconCommand::parse("timers list group 3"), Some(RconCommand::List(ListFilter::Group(3))));
+        assert_eq!(
+            RconCommand::parse("timers list callback OnTick"),
+            Some(RconCommand::List(ListFilter::Callback("OnTick".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_list_filters() {
+        let manager = TimerManager::for_tests();
+        let create = |owner_amx: usize, callback: &str| {
+            manager.create_timer(owner_amx, 60_000, true, callback.to_string(), None)
+                .expect("Failed to create timer")
+        };
+        let grouped = create(0x23, "OnTickA");
+        create(0x23, "OnTickA");
+        let other_script = create(0x24, "OnTickB");
+        manager.set_group(0x23, grouped, Some(4)).expect("Failed to group timer");
+
+        /* header, one row per timer, then the total */
+        let by_script = execute(manager, &RconCommand::List(ListFilter::Amx(0x23)));
+        assert_eq!(by_script.len(), 4);
+        assert_eq!(by_script.last().map(String::as_str), Some("2 timers"));
+
+        let by_group = execute(manager, &RconCommand::List(ListFilter::Group(4)));
+        assert_eq!(by_group.len(), 3);
+        assert!(by_group[1].trim_start().starts_with(&grouped.to_string()));
+
+        let by_callback = execute(manager, &RconCommand::List(ListFilter::Callback("ontickb".to_string())));
+        assert_eq!(by_callback.len(), 3);
+        assert!(by_callback[1].trim_start().starts_with(&other_script.to_string()));
+
+        assert_eq!(
+            execute(manager, &RconCommand::List(ListFilter::Group(5))),
+            vec!["No matching timers".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_list_truncated() {
+        let manager = TimerManager::for_tests();
+        for _ in 0..MAX_LISTED_TIMERS + 5 {
+            manager.create_timer(0x25, 60_000, true, "OnFlood".to_string(), None).expect("Failed to create timer");
+        }
+
+        let lines = execute(manager, &RconCommand::List(ListFilter::All));
+        assert_eq!(lines.len(), MAX_LISTED_TIMERS + 2);
+        assert_eq!(lines.last().map(String::as_str), Some("... and 5 more, narrow the list with a filter"));
+    }
+
+    #[test]
+    fn test_commands_on_missing_timer() {
+        let manager = TimerManager::for_tests();
+
+        for command in [RconCommand::Kill(-1), RconCommand::Pause(-1), RconCommand::Resume(-1)] {
+            let lines = execute(manager, &command);
+            assert_eq!(lines, vec![TimerError::TimerNotFound(-1).to_user_message()]);
+        }
+        assert_eq!(execute(manager, &RconCommand::Info(-1)), vec!["Timer -1 not found".to_string()]);
+
+        let timer_id = manager.create_timer(0x26, 60_000, true, "OnControl".to_string(), None)
+            .expect("Failed to create timer");
+        assert_eq!(execute(manager, &RconCommand::Pause(timer_id)), vec![format!("Timer {} paused", timer_id)]);
+        assert!(manager.is_paused(timer_id).unwrap());
+        assert_eq!(execute(manager, &RconCommand::Kill(timer_id)), vec![format!("Timer {} killed", timer_id)]);
+        assert!(manager.get_timer_info(timer_id).is_none());
+    }
+}
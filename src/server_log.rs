@@ -0,0 +1,200 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write};
+use std::time::{Duration, Instant};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use tracing::callsite::Identifier;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+const MAX_PENDING_LINES: usize = 1000;
+const MAX_TRACKED_KEYS: usize = 10000;
+
+/* samp's logprintf, only called from the server thread */
+static SERVER_LOGGER: OnceCell<Box<dyn log::Log>> = OnceCell::new();
+
+lazy_static::lazy_static! {
+    static ref PENDING_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+}
+
+pub fn set_logger(logger: Box<dyn log::Log>) {
+    if SERVER_LOGGER.set(logger).is_err() {
+        tracing::warn!("Server log output already initialized, ignoring");
+    }
+}
+
+/* writes queued lines to server_log.txt; must only be called from the server thread */
+pub fn flush() -> usize {
+    let lines: Vec<String> = PENDING_LINES.lock().drain(..).collect();
+    for line in &lines {
+        print(line);
+    }
+    lines.len()
+}
+
+/* writes a line to the console and server_log.txt right away; must only be called from the server thread */
+pub fn print(line: &str) {
+    if let Some(logger) = SERVER_LOGGER.get() {
+        logger.log(&log::Record::builder().args(format_args!("{}", line)).build());
+    }
+}
+
+/* repeated messages are keyed by where they were logged and the timer they concern */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MessageKey {
+    Timer(Identifier, i32),
+    Message(String),
+}
+
+struct RateLimiter {
+    window: Duration,
+    entries: HashMap<MessageKey, (Instant, u64)>,
+}
+
+impl RateLimiter {
+    fn new(window: Duration) -> Self {
+        RateLimiter {
+            window,
+            entries: HashMap::new(),
+        }
+    }
+
+    /* None if the message is suppressed, otherwise how many were suppressed before it */
+    fn check(&mut self, key: MessageKey, now: Instant) -> Option<u64> {
+        if self.window.is_zero() {
+            return Some(0);
+        }
+
+        if self.entries.len() >= MAX_TRACKED_KEYS {
+            let window = self.window;
+            self.entries.retain(|_, (last, _)| now.duration_since(*last) < window);
+        }
+
+        match self.entries.get_mut(&key) {
+            Some((last, suppressed)) if now.duration_since(*last) < self.window => {
+                *suppressed += 1;
+                None
+            }
+            Some(entry) => {
+                let suppressed = entry.1;
+                *entry = (now, 0);
+                Some(suppressed)
+            }
+            None => {
+                self.entries.insert(key, (now, 0));
+                Some(0)
+            }
+        }
+    }
+}
+
+/* timer a span was opened for, see the "timer" spans around timer tasks and callbacks */
+struct SpanTimerId(i32);
+
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: String,
+    timer_id: Option<i32>,
+}
+
+impl Visit for EventVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == "timer_id" {
+            self.timer_id = i32::try_from(value).ok();
+        }
+        self.record_debug(field, &value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/* queues warnings and errors for server_log.txt, flushed from process_tick */
+pub struct ServerLogLayer {
+    limiter: Mutex<RateLimiter>,
+}
+
+impl ServerLogLayer {
+    pub fn new(rate_limit: Duration) -> Self {
+        ServerLogLayer {
+            limiter: Mutex::new(RateLimiter::new(rate_limit)),
+        }
+    }
+}
+
+impl<S> Layer<S> for ServerLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = EventVisitor::default();
+        attrs.record(&mut visitor);
+
+        if let (Some(timer_id), Some(span)) = (visitor.timer_id, ctx.span(id)) {
+            span.extensions_mut().insert(SpanTimerId(timer_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        let timer_id = visitor.timer_id.or_else(|| {
+            ctx.event_scope(event)?
+                .find_map(|span| span.extensions().get::<SpanTimerId>().map(|timer| timer.0))
+        });
+
+        let key = match timer_id {
+            Some(timer_id) => MessageKey::Timer(event.metadata().callsite(), timer_id),
+            None => MessageKey::Message(visitor.message.clone()),
+        };
+
+        let Some(suppressed) = self.limiter.lock().check(key, Instant::now()) else {
+            return;
+        };
+
+        let mut line = format!("[timers] {}: {}{}", event.metadata().level(), visitor.message, visitor.fields);
+        if suppressed > 0 {
+            let _ = write!(line, " ({} similar messages suppressed)", suppressed);
+        }
+
+        let mut pending = PENDING_LINES.lock();
+        if pending.len() >= MAX_PENDING_LINES {
+            pending.pop_front();
+        }
+        pending.push_back(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(Duration::from_secs(60));
+        let key = MessageKey::Message("Timer 5 callback failed".to_string());
+        let other = MessageKey::Message("Timer 6 callback failed".to_string());
+
+        assert_eq!(limiter.check(key.clone(), start), Some(0));
+        assert_eq!(limiter.check(key.clone(), start + Duration::from_secs(1)), None);
+        assert_eq!(limiter.check(key.clone(), start + Duration::from_secs(30)), None);
+        assert_eq!(limiter.check(other, start + Duration::from_secs(30)), Some(0));
+        assert_eq!(limiter.check(key.clone(), start + Duration::from_secs(61)), Some(2));
+
+        let mut unlimited = RateLimiter::new(Duration::ZERO);
+        assert_eq!(unlimited.check(key.clone(), start), Some(0));
+        assert_eq!(unlimited.check(key, start), Some(0));
+    }
+}
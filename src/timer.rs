@@ -1,46 +1,231 @@
 use std::sync::atomic::{AtomicI32, AtomicBool, AtomicUsize, Ordering};
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
-use tokio::time::{sleep, Instant};
+use tokio::sync::Notify;
+use tokio::time::{sleep_until, Instant};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
 
 use crate::error::{TimerError, TimerResult};
-use crate::callback::{CallbackData, execute_callback};
+use crate::callback::{self, CallbackData, DispatchStats, execute_callback};
+use crate::config;
+use crate::cron::{CronSchedule, CronTimezone};
+use crate::dump::{self, TimerSnapshot};
+#[cfg(feature = "metrics")]
+use crate::metrics;
 
 static TIMER_ID_COUNTER: AtomicI32 = AtomicI32::new(1);
 static SHUTDOWN_FLAG: AtomicBool = AtomicBool::new(false);
 static ACTIVE_TIMER_COUNT: AtomicUsize = AtomicUsize::new(0);
 
-const MAX_TIMERS: usize = 10000;
-const MAX_DELAY_MS: i32 = 2_147_483_647; /* max i32 value */
+/* cron timers recheck the wall clock at least this often to follow clock changes */
+const CRON_RESOLUTION_MS: i32 = 60_000;
+
+/* recent callback durations kept per timer to estimate the p99 */
+const STATS_SAMPLE_SIZE: usize = 1000;
+
+/* which limit Timer_GetQuota refers to, both are set in timers.toml */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    Script, /* timers owned by one script */
+    Group,  /* timers in one group of a script */
+}
+
+impl QuotaKind {
+    pub fn from_i32(val: i32) -> Option<Self> {
+        match val {
+            0 => Some(QuotaKind::Script),
+            1 => Some(QuotaKind::Group),
+            _ => None,
+        }
+    }
+}
+
+/* how a persistent timer's next execution is restored after a restart */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PersistMode {
+    Remaining, /* the time left when saved, the clock stops while the server is down */
+    Deadline,  /* the wall-clock time it was due, overdue timers fire right away */
+}
+
+impl PersistMode {
+    pub fn from_i32(val: i32) -> Option<Self> {
+        match val {
+            1 => Some(PersistMode::Remaining),
+            2 => Some(PersistMode::Deadline),
+            _ => None,
+        }
+    }
+}
+
+/* what a fixed-rate timer does when it falls behind by one or more periods */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissedTickPolicy {
+    Burst, /* fire back-to-back until caught up */
+    Skip,  /* drop missed periods, stay aligned to the original schedule */
+    Delay, /* restart the schedule from now */
+}
+
+impl MissedTickPolicy {
+    pub fn from_i32(val: i32) -> Option<Self> {
+        match val {
+            0 => Some(MissedTickPolicy::Burst),
+            1 => Some(MissedTickPolicy::Skip),
+            2 => Some(MissedTickPolicy::Delay),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScheduleMode {
+    /* next period starts after the callback has run */
+    #[default]
+    FixedDelay,
+    /* periods are anchored to absolute deadlines and do not drift */
+    FixedRate(MissedTickPolicy),
+    /* fires at wall-clock times matching a cron expression */
+    Cron(CronSchedule),
+}
+
+/* optional behaviour on top of delay/repeat, extended as timers gain features */
+#[derive(Debug, Clone, Default)]
+pub struct TimerOptions {
+    pub max_executions: Option<u64>,
+    /* first execution after this many ms instead of delay_ms, 0 fires immediately */
+    pub initial_delay_ms: Option<u64>,
+    pub schedule: ScheduleMode,
+    /* killed automatically when this player disconnects */
+    pub player: Option<i32>,
+    pub group: Option<i32>,
+    pub persist: Option<PersistMode>,
+    /* created paused, the initial delay starts counting on resume */
+    pub paused: bool,
+}
+
+/* callback outcomes of one timer, recorded after every execution */
+#[derive(Debug, Default)]
+pub struct TimerStats {
+    successes: u64,
+    failures: u64,
+    total_duration: Duration,
+    min_duration: Option<Duration>,
+    max_duration: Duration,
+    recent_durations: VecDeque<Duration>,
+    last_error: Option<String>,
+}
+
+impl TimerStats {
+    pub fn record_success(&mut self, duration: Duration) {
+        self.successes += 1;
+        self.total_duration += duration;
+        self.min_duration = Some(self.min_duration.map_or(duration, |min| min.min(duration)));
+        self.max_duration = self.max_duration.max(duration);
+
+        if self.recent_durations.len() >= STATS_SAMPLE_SIZE {
+            self.recent_durations.pop_front();
+        }
+        self.recent_durations.push_back(duration);
+    }
+
+    pub fn record_failure(&mut self, error: &TimerError) {
+        self.failures += 1;
+        self.last_error = Some(error.to_user_message());
+    }
+
+    pub fn average_duration(&self) -> Duration {
+        match self.successes {
+            0 => Duration::ZERO,
+            successes => Duration::from_nanos((self.total_duration.as_nanos() / successes as u128) as u64),
+        }
+    }
+
+    /* the p99 only covers the last STATS_SAMPLE_SIZE executions */
+    pub fn summary(&self) -> TimerStatsSummary {
+        let mut recent: Vec<Duration> = self.recent_durations.iter().copied().collect();
+        recent.sort_unstable();
+        let p99_rank = (recent.len() * 99).div_ceil(100);
+
+        TimerStatsSummary {
+            successes: self.successes,
+            failures: self.failures,
+            min_duration: self.min_duration.unwrap_or_default(),
+            avg_duration: self.average_duration(),
+            max_duration: self.max_duration,
+            p99_duration: recent.get(p99_rank.saturating_sub(1)).copied().unwrap_or_default(),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimerStatsSummary {
+    pub successes: u64,
+    pub failures: u64,
+    pub min_duration: Duration,
+    pub avg_duration: Duration,
+    pub max_duration: Duration,
+    pub p99_duration: Duration,
+    pub last_error: Option<String>,
+}
 
 #[derive(Debug)]
 pub struct Timer {
     pub id: i32,
+    pub owner_amx: usize,
     pub delay_ms: u64,
+    pub initial_delay_ms: u64,
     pub repeat: bool,
     pub callback: String,
     pub params: Option<CallbackData>,
     pub created_at: Instant,
     pub last_execution: Option<Instant>,
     pub execution_count: u64,
+    pub executions_left: Option<u64>,
+    pub schedule: ScheduleMode,
+    pub period_start: Instant,
+    pub next_deadline: Instant,
+    /* the first period still runs on initial_delay_ms */
+    pub initial_period: bool,
+    pub wall_deadline: Option<DateTime<Utc>>, /* cron timers only */
+    pub group: Option<i32>,
+    pub player: Option<i32>,
+    pub paused_at: Option<Instant>,
+    pub persist: Option<PersistMode>,
+    pub stats: TimerStats,
+    pub control: Arc<Notify>, /* wakes the running task after a state change */
     pub task_handle: Option<JoinHandle<()>>,
 }
 
 impl Timer {
     pub fn new(
+        owner_amx: usize,
         delay_ms: i32,
         repeat: bool,
         callback: String,
         params: Option<CallbackData>,
+        options: TimerOptions,
     ) -> TimerResult<Self> {
-        if delay_ms <= 0 || delay_ms > MAX_DELAY_MS {
+        if delay_ms <= 0 {
             return Err(TimerError::InvalidDelay(delay_ms));
         }
 
+        if options.max_executions == Some(0) {
+            return Err(TimerError::ParameterValidation(
+                "Execution count must be positive".to_string()
+            ));
+        }
+
         if !crate::callback::is_valid_callback_name(&callback) {
             return Err(TimerError::InvalidCallback(callback));
         }
@@ -49,11 +234,28 @@ impl Timer {
             params.validate()?;
         }
 
+        if options.persist.is_some() {
+            if let ScheduleMode::Cron(_) = options.schedule {
+                return Err(TimerError::ParameterValidation("Cron timers cannot be persistent".to_string()));
+            }
+            if options.player.is_some() {
+                return Err(TimerError::ParameterValidation("Timers bound to a player cannot be persistent".to_string()));
+            }
+        }
+
+        let wall_deadline = match options.schedule {
+            ScheduleMode::Cron(cron) => Some(cron.next_after(Utc::now()).ok_or_else(|| {
+                TimerError::ParameterValidation("Cron expression never matches".to_string())
+            })?),
+            _ => None,
+        };
+
         /* check resource limits */
         let current_count = ACTIVE_TIMER_COUNT.load(Ordering::Acquire);
-        if current_count >= MAX_TIMERS {
+        let max_timers = config::get().limits.max_timers;
+        if current_count >= max_timers {
             return Err(TimerError::ResourceExhaustion(
-                format!("Maximum timer limit reached: {}", MAX_TIMERS)
+                format!("Maximum timer limit reached: {}", max_timers)
             ));
         }
 
@@ -67,34 +269,356 @@ impl Timer {
             return Err(TimerError::IdOverflow);
         }
 
-        Ok(Timer {
+        let now = Instant::now();
+        let initial_delay_ms = options.initial_delay_ms.unwrap_or(delay_ms as u64);
+
+        let mut timer = Timer {
             id,
+            owner_amx,
             delay_ms: delay_ms as u64,
+            initial_delay_ms,
             repeat,
             callback,
             params,
-            created_at: Instant::now(),
+            created_at: now,
             last_execution: None,
             execution_count: 0,
+            executions_left: options.max_executions,
+            schedule: options.schedule,
+            period_start: now,
+            next_deadline: now + Duration::from_millis(initial_delay_ms),
+            initial_period: initial_delay_ms != delay_ms as u64,
+            wall_deadline,
+            group: options.group,
+            player: options.player,
+            paused_at: options.paused.then_some(now),
+            persist: options.persist,
+            stats: TimerStats::default(),
+            control: Arc::new(Notify::new()),
             task_handle: None,
-        })
+        };
+        timer.resync();
+
+        Ok(timer)
     }
 
-    pub fn mark_execution(&mut self) {
+    pub fn mark_execution(&mut self, duration: Duration) {
         self.last_execution = Some(Instant::now());
         self.execution_count += 1;
+        self.stats.record_success(duration);
+    }
+
+    pub fn mark_failure(&mut self, error: &TimerError) {
+        self.stats.record_failure(error);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn info(&self) -> TimerInfo {
+        /* cron timers only recheck the clock every delay_ms, the time until the next run says more */
+        let delay_ms = match self.schedule {
+            ScheduleMode::Cron(_) => self.remaining().as_millis().min(u64::MAX as u128) as u64,
+            _ => self.delay_ms,
+        };
+
+        TimerInfo {
+            owner_amx: self.owner_amx,
+            delay_ms,
+            initial_delay_ms: self.initial_delay_ms,
+            repeat: self.repeat,
+            callback: self.callback.clone(),
+            elapsed: self.created_at.elapsed(),
+            execution_count: self.execution_count,
+            last_execution_age: self.last_execution.map(|at| at.elapsed()),
+            remaining: self.remaining(),
+            paused: self.is_paused(),
+            group: self.group,
+            player: self.player,
+        }
+    }
+
+    /* time left until the next execution, frozen while paused */
+    pub fn remaining(&self) -> Duration {
+        let now = self.paused_at.unwrap_or_else(Instant::now);
+        self.next_deadline.saturating_duration_since(now)
+    }
+
+    pub fn pause(&mut self) -> bool {
+        if self.paused_at.is_some() {
+            return false;
+        }
+        self.paused_at = Some(Instant::now());
+        true
+    }
+
+    pub fn resume(&mut self) -> bool {
+        match self.paused_at.take() {
+            /* cron times that passed while paused are skipped */
+            Some(_) if self.wall_deadline.is_some() => {
+                self.rearm();
+                true
+            }
+            Some(paused_at) => {
+                let paused_for = paused_at.elapsed();
+                self.period_start += paused_for;
+                self.next_deadline += paused_for;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /* interval changes apply to the period already in progress, unless it is the initial delay */
+    pub fn set_delay(&mut self, delay_ms: u64) {
+        self.delay_ms = delay_ms;
+        if !self.initial_period {
+            self.next_deadline = self.period_start + Duration::from_millis(delay_ms);
+        }
+    }
+
+    /* starts a full period, counted from the pause point if paused */
+    pub fn rearm(&mut self) {
+        self.initial_period = false;
+        if let ScheduleMode::Cron(cron) = self.schedule {
+            self.wall_deadline = cron.next_after(Utc::now());
+            self.resync();
+            return;
+        }
+
+        self.period_start = self.paused_at.unwrap_or_else(Instant::now);
+        self.next_deadline = self.period_start + Duration::from_millis(self.delay_ms);
+    }
+
+    /* schedules the next period after an execution, false if there is none */
+    pub fn advance(&mut self) -> bool {
+        self.initial_period = false;
+        let policy = match self.schedule {
+            ScheduleMode::FixedDelay => {
+                self.rearm();
+                return true;
+            }
+            ScheduleMode::FixedRate(policy) => policy,
+            ScheduleMode::Cron(cron) => {
+                /* times missed during a slow callback are skipped, not caught up */
+                let after = self.wall_deadline.map_or_else(Utc::now, |previous| previous.max(Utc::now()));
+                self.wall_deadline = cron.next_after(after);
+                self.resync();
+                return self.wall_deadline.is_some();
+            }
+        };
+
+        let delay = Duration::from_millis(self.delay_ms);
+        let now = self.paused_at.unwrap_or_else(Instant::now);
+        let mut next = self.next_deadline + delay;
+
+        if next <= now {
+            match policy {
+                MissedTickPolicy::Burst => {}
+                MissedTickPolicy::Skip => {
+                    let missed = (now - next).as_nanos() / delay.as_nanos() + 1;
+                    next += Duration::from_nanos((delay.as_nanos() * missed) as u64);
+                }
+                MissedTickPolicy::Delay => next = now + delay,
+            }
+        }
+
+        self.period_start = next - delay;
+        self.next_deadline = next;
+        true
+    }
+
+    /* maps the wall-clock deadline of a cron timer onto the monotonic clock */
+    pub fn resync(&mut self) {
+        let Some(wall_deadline) = self.wall_deadline else {
+            return;
+        };
+        if self.paused_at.is_some() {
+            return;
+        }
+
+        let now = Instant::now();
+        let until = (wall_deadline - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+        self.period_start = now;
+        self.next_deadline = now + until;
+    }
+
+    /* when the task should next wake, cron timers wake early to recheck the wall clock */
+    pub fn wake_deadline(&self) -> Instant {
+        match self.schedule {
+            ScheduleMode::Cron(_) => {
+                let recheck = Instant::now() + Duration::from_millis(CRON_RESOLUTION_MS as u64);
+                self.next_deadline.min(recheck)
+            }
+            _ => self.next_deadline,
+        }
+    }
+}
+#[derive(Debug, Clone)]
+pub struct TimerInfo {
+    pub owner_amx: usize,
+    pub delay_ms: u64,
+    pub initial_delay_ms: u64,
+    pub repeat: bool,
+    pub callback: String,
+    pub elapsed: Duration,
+    pub execution_count: u64,
+    pub last_execution_age: Option<Duration>,
+    pub remaining: Duration,
+    pub paused: bool,
+    pub group: Option<i32>,
+    pub player: Option<i32>,
+}
+
+/* ms from `now` until a unix timestamp, late timestamps fire immediately if allowed */
+fn delay_until_timestamp(timestamp: i64, now: DateTime<Utc>, fire_if_late: bool) -> TimerResult<u64> {
+    if timestamp < now.timestamp() && !fire_if_late {
+        return Err(TimerError::TimestampInPast(timestamp));
     }
+
+    let delay_ms = timestamp.saturating_mul(1000).saturating_sub(now.timestamp_millis());
+    Ok(delay_ms.max(0) as u64)
+}
+
+type PanicHook = dyn Fn(&std::panic::PanicHookInfo<'_>) + Send + Sync;
+
+/* groups are scoped to the script that owns the timers */
+type GroupKey = (usize, i32);
+
+/* the keys a timer was indexed under, kept outside its lock so it can always be unindexed */
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    owner_amx: usize,
+    group: Option<i32>,
+    player: Option<i32>,
 }
+
+/* secondary indexes of timer ids, so bulk operations avoid scanning every timer */
+#[derive(Default)]
+struct TimerIndexes {
+    entries: DashMap<i32, IndexEntry>,
+    owners: DashMap<usize, HashSet<i32>>,
+    groups: DashMap<GroupKey, HashSet<i32>>,
+    players: DashMap<i32, HashSet<i32>>,
+}
+
+impl TimerIndexes {
+    fn insert<K: Hash + Eq>(index: &DashMap<K, HashSet<i32>>, key: K, timer_id: i32) {
+        index.entry(key).or_default().insert(timer_id);
+    }
+
+    fn remove<K: Hash + Eq>(index: &DashMap<K, HashSet<i32>>, key: K, timer_id: i32) {
+        if let Some(mut timer_ids) = index.get_mut(&key) {
+            timer_ids.remove(&timer_id);
+        }
+        index.remove_if(&key, |_, timer_ids| timer_ids.is_empty());
+    }
+
+    fn timer_ids<K: Hash + Eq>(index: &DashMap<K, HashSet<i32>>, key: K) -> Vec<i32> {
+        index
+            .get(&key)
+            .map(|timer_ids| timer_ids.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn count<K: Hash + Eq>(index: &DashMap<K, HashSet<i32>>, key: K) -> usize {
+        index.get(&key).map_or(0, |timer_ids| timer_ids.len())
+    }
+
+    /* checked and inserted under the key's entry lock, so concurrent inserts can't overshoot the quota (0 = none) */
+    fn insert_within_quota<K: Hash + Eq>(index: &DashMap<K, HashSet<i32>>, key: K, timer_id: i32, quota: usize) -> bool {
+        let mut timer_ids = index.entry(key).or_default();
+        if quota > 0 && !timer_ids.contains(&timer_id) && timer_ids.len() >= quota {
+            return false;
+        }
+        timer_ids.insert(timer_id);
+        true
+    }
+
+    fn add_timer(&self, timer: &Timer, script_quota: usize, group_quota: usize) -> TimerResult<()> {
+        let entry = IndexEntry {
+            owner_amx: timer.owner_amx,
+            group: timer.group,
+            player: timer.player,
+        };
+
+        if !Self::insert_within_quota(&self.owners, entry.owner_amx, timer.id, script_quota) {
+            return Err(TimerError::ScriptQuotaExceeded(script_quota));
+        }
+        if let Some(group) = entry.group {
+            if !Self::insert_within_quota(&self.groups, (entry.owner_amx, group), timer.id, group_quota) {
+                Self::remove(&self.owners, entry.owner_amx, timer.id);
+                return Err(TimerError::GroupQuotaExceeded(group_quota));
+            }
+        }
+        if let Some(player) = entry.player {
+            Self::insert(&self.players, player, timer.id);
+        }
+
+        self.entries.insert(timer.id, entry);
+        Ok(())
+    }
+
+    fn set_group(&self, timer_id: i32, group: Option<i32>, group_quota: usize) -> TimerResult<()> {
+        let mut entry = self.entries.get_mut(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        if entry.group == group {
+            return Ok(());
+        }
+
+        if let Some(group) = group {
+            if !Self::insert_within_quota(&self.groups, (entry.owner_amx, group), timer_id, group_quota) {
+                return Err(TimerError::GroupQuotaExceeded(group_quota));
+            }
+        }
+        if let Some(previous) = entry.group {
+            Self::remove(&self.groups, (entry.owner_amx, previous), timer_id);
+        }
+
+        entry.group = group;
+        Ok(())
+    }
+
+    /* drops every index entry of a timer that is going away */
+    fn remove_timer(&self, timer_id: i32) -> Option<IndexEntry> {
+        let (_, entry) = self.entries.remove(&timer_id)?;
+
+        Self::remove(&self.owners, entry.owner_amx, timer_id);
+        if let Some(group) = entry.group {
+            Self::remove(&self.groups, (entry.owner_amx, group), timer_id);
+        }
+        if let Some(player) = entry.player {
+            Self::remove(&self.players, player, timer_id);
+        }
+
+        Some(entry)
+    }
+
+    fn clear(&self) {
+        self.entries.clear();
+        self.owners.clear();
+        self.groups.clear();
+        self.players.clear();
+    }
+}
+
 pub struct TimerManager {
     timers: Arc<DashMap<i32, Arc<RwLock<Timer>>>>,
+    indexes: Arc<TimerIndexes>,
+    /* limits.max_timers_per_script and max_timers_per_group, 0 means no limit */
+    script_quota: usize,
+    group_quota: usize,
     runtime: Arc<tokio::runtime::Runtime>,
     shutdown_complete: Arc<AtomicBool>,
+    previous_panic_hook: Mutex<Option<Arc<PanicHook>>>,
+    /* set with Timer_SetCronTimezone, scripts without one use cron.timezone from timers.toml */
+    cron_timezones: DashMap<usize, CronTimezone>,
 }
 
 impl TimerManager {
     pub fn new() -> TimerResult<Self> {
         let runtime = tokio::runtime::Builder::new_multi_thread()
-            .worker_threads(2) 
+            .worker_threads(config::get().runtime.worker_threads)
             .thread_name("samp-timer")
             .enable_all()
             .build()
@@ -102,36 +626,78 @@ impl TimerManager {
 
         Ok(TimerManager {
             timers: Arc::new(DashMap::new()),
+            indexes: Arc::new(TimerIndexes::default()),
+            script_quota: config::get().limits.max_timers_per_script,
+            group_quota: config::get().limits.max_timers_per_group,
             runtime: Arc::new(runtime),
             shutdown_complete: Arc::new(AtomicBool::new(false)),
+            previous_panic_hook: Mutex::new(None),
+            cron_timezones: DashMap::new(),
         })
     }
 
     pub fn create_timer(
         &self,
+        owner_amx: usize,
         delay_ms: i32,
         repeat: bool,
         callback: String,
         params: Option<CallbackData>,
+    ) -> TimerResult<i32> {
+        self.create_timer_with_options(owner_amx, delay_ms, repeat, callback, params, TimerOptions::default())
+    }
+
+    pub fn create_timer_with_options(
+        &self,
+        owner_amx: usize,
+        delay_ms: i32,
+        repeat: bool,
+        callback: String,
+        params: Option<CallbackData>,
+        options: TimerOptions,
+    ) -> TimerResult<i32> {
+        let result = self.start_timer(owner_amx, delay_ms, repeat, callback, params, options);
+
+        #[cfg(feature = "metrics")]
+        match &result {
+            Ok(_) => metrics::timer_created(owner_amx),
+            Err(e) => metrics::failure(e),
+        }
+
+        result
+    }
+
+    fn start_timer(
+        &self,
+        owner_amx: usize,
+        delay_ms: i32,
+        repeat: bool,
+        callback: String,
+        params: Option<CallbackData>,
+        options: TimerOptions,
     ) -> TimerResult<i32> {
         if SHUTDOWN_FLAG.load(Ordering::Acquire) {
             return Err(TimerError::SystemShutdown);
         }
 
-        let timer = Timer::new(delay_ms, repeat, callback, params)?;
+        let timer = Timer::new(owner_amx, delay_ms, repeat, callback, params, options)?;
         let timer_id = timer.id;
 
         let timers_ref = Arc::clone(&self.timers);
+        let indexes_ref = Arc::clone(&self.indexes);
+        self.indexes.add_timer(&timer, self.script_quota, self.group_quota)?;
         let timer_arc = Arc::new(RwLock::new(timer));
 
         self.timers.insert(timer_id, Arc::clone(&timer_arc));
         ACTIVE_TIMER_COUNT.fetch_add(1, Ordering::Release);
 
+        /* the span ties the task's log messages to the timer, see server_log */
         let task_handle = self.runtime.spawn(Self::timer_task(
             timer_id,
             Arc::clone(&timer_arc),
             timers_ref,
-        ));
+            indexes_ref,
+        ).instrument(tracing::error_span!("timer", timer_id)));
 
         /* update task handle with timeout protection */
         match timer_arc.try_write_for(Duration::from_millis(100)) {
@@ -141,6 +707,7 @@ impl TimerManager {
             None => {
                 task_handle.abort();
                 self.timers.remove(&timer_id);
+                self.indexes.remove_timer(timer_id);
                 ACTIVE_TIMER_COUNT.fetch_sub(1, Ordering::Release);
                 tracing::error!("Failed to acquire write lock for timer {} within timeout", timer_id);
                 return Err(TimerError::Internal("Failed to acquire timer write lock within timeout".to_string()));
@@ -151,20 +718,268 @@ impl TimerManager {
         Ok(timer_id)
     }
 
+    /* one-shot timer firing at a unix timestamp, converted to a monotonic deadline now */
+    pub fn create_timer_at(
+        &self,
+        owner_amx: usize,
+        timestamp: i64,
+        fire_if_late: bool,
+        callback: String,
+        params: Option<CallbackData>,
+    ) -> TimerResult<i32> {
+        let delay_ms = delay_until_timestamp(timestamp, Utc::now(), fire_if_late)?;
+        let options = TimerOptions {
+            initial_delay_ms: Some(delay_ms),
+            ..TimerOptions::default()
+        };
+        self.create_timer_with_options(owner_amx, delay_ms.clamp(1, i32::MAX as u64) as i32, false, callback, params, options)
+    }
+
+    /* only affects cron timers the script creates afterwards */
+    pub fn set_cron_timezone(&self, owner_amx: usize, timezone: CronTimezone) {
+        self.cron_timezones.insert(owner_amx, timezone);
+    }
+
+    pub fn clear_cron_timezone(&self, owner_amx: usize) {
+        self.cron_timezones.remove(&owner_amx);
+    }
+
+    /* repeating timer driven by a cron expression in the script's time zone */
+    pub fn create_cron_timer(
+        &self,
+        owner_amx: usize,
+        expression: &str,
+        callback: String,
+        params: Option<CallbackData>,
+    ) -> TimerResult<i32> {
+        let timezone = self.cron_timezones.get(&owner_amx).map_or_else(|| config::get().cron.timezone(), |timezone| *timezone);
+        let schedule = CronSchedule::parse(expression, timezone)?;
+        let options = TimerOptions {
+            schedule: ScheduleMode::Cron(schedule),
+            ..TimerOptions::default()
+        };
+        self.create_timer_with_options(owner_amx, CRON_RESOLUTION_MS, true, callback, params, options)
+    }
+
     pub fn kill_timer(&self, timer_id: i32) -> TimerResult<()> {
-        if let Some((_, timer_arc)) = self.timers.remove(&timer_id) {
-            if let Some(timer_guard) = timer_arc.try_read_for(Duration::from_millis(50)) {
-                if let Some(ref handle) = timer_guard.task_handle {
-                    handle.abort();
-                }
+        let (_, timer_arc) = self.timers.remove(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let indexed = self.indexes.remove_timer(timer_id);
+        ACTIVE_TIMER_COUNT.fetch_sub(1, Ordering::Release);
+
+        let aborted = timer_arc
+            .try_read_for(Duration::from_millis(50))
+            .map(|timer_guard| Self::abort_task(&timer_guard))
+            .is_some();
+        if !aborted {
+            /* the task still has to stop, once whoever holds the lock lets go of it */
+            tracing::warn!("Timer {} is locked, stopping its task in the background", timer_id);
+            self.runtime.spawn_blocking(move || Self::abort_task(&timer_arc.read()));
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(indexed) = indexed {
+            metrics::timer_killed(indexed.owner_amx);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = indexed;
+
+        tracing::debug!("Timer {} killed and removed", timer_id);
+        Ok(())
+    }
+
+    fn abort_task(timer: &Timer) {
+        if let Some(ref handle) = timer.task_handle {
+            handle.abort();
+        }
+    }
+
+    pub fn pause_timer(&self, owner_amx: Option<usize>, timer_id: i32) -> TimerResult<()> {
+        self.update_timer(owner_amx, timer_id, |timer| {
+            if timer.pause() {
+                tracing::debug!("Timer {} paused with {:?} remaining", timer_id, timer.remaining());
             }
+            Ok(())
+        })
+    }
 
-            ACTIVE_TIMER_COUNT.fetch_sub(1, Ordering::Release);
-            tracing::debug!("Timer {} killed and removed", timer_id);
+    pub fn resume_timer(&self, owner_amx: Option<usize>, timer_id: i32) -> TimerResult<()> {
+        self.update_timer(owner_amx, timer_id, |timer| {
+            if timer.resume() {
+                tracing::debug!("Timer {} resumed with {:?} remaining", timer_id, timer.remaining());
+            }
             Ok(())
-        } else {
-            Err(TimerError::TimerNotFound(timer_id))
+        })
+    }
+
+    pub fn is_paused(&self, timer_id: i32) -> TimerResult<bool> {
+        let timer_entry = self.timers.get(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let paused = timer_entry.read().is_paused();
+        Ok(paused)
+    }
+
+    /* None for timers without an execution limit */
+    pub fn get_executions_left(&self, timer_id: i32) -> TimerResult<Option<u64>> {
+        let timer_entry = self.timers.get(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let executions_left = timer_entry.read().executions_left;
+        Ok(executions_left)
+    }
+
+    pub fn get_remaining(&self, timer_id: i32) -> TimerResult<Duration> {
+        let timer_entry = self.timers.get(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let remaining = timer_entry.read().remaining();
+        Ok(remaining)
+    }
+
+    pub fn set_interval(&self, owner_amx: Option<usize>, timer_id: i32, delay_ms: i32) -> TimerResult<()> {
+        if delay_ms <= 0 {
+            return Err(TimerError::InvalidDelay(delay_ms));
+        }
+
+        self.update_timer(owner_amx, timer_id, |timer| {
+            Self::reject_cron(timer)?;
+            timer.set_delay(delay_ms as u64);
+            tracing::debug!("Timer {} interval changed to {}ms", timer_id, delay_ms);
+            Ok(())
+        })
+    }
+
+    pub fn restart_timer(&self, owner_amx: Option<usize>, timer_id: i32) -> TimerResult<()> {
+        self.update_timer(owner_amx, timer_id, |timer| {
+            timer.rearm();
+            tracing::debug!("Timer {} restarted", timer_id);
+            Ok(())
+        })
+    }
+
+    pub fn set_schedule_mode(&self, owner_amx: Option<usize>, timer_id: i32, schedule: ScheduleMode) -> TimerResult<()> {
+        self.update_timer(owner_amx, timer_id, |timer| {
+            Self::reject_cron(timer)?;
+            timer.schedule = schedule;
+            tracing::debug!("Timer {} schedule mode set to {:?}", timer_id, schedule);
+            Ok(())
+        })
+    }
+
+    /* cron timers follow their expression, not an interval */
+    fn reject_cron(timer: &Timer) -> TimerResult<()> {
+        match timer.schedule {
+            ScheduleMode::Cron(_) => Err(TimerError::ParameterValidation(
+                format!("Timer {} uses a cron schedule", timer.id)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /* scripts may only regroup or persist their own timers */
+    fn check_owner(timer: &Timer, owner_amx: usize) -> TimerResult<()> {
+        if timer.owner_amx != owner_amx {
+            return Err(TimerError::NotOwner(timer.id));
         }
+        Ok(())
+    }
+
+    /* applies a change to the timer state and wakes its task to pick it up */
+    /* owner_amx is the calling script, None for the server admin through RCON */
+    fn update_timer<F: FnOnce(&mut Timer) -> TimerResult<()>>(&self, owner_amx: Option<usize>, timer_id: i32, update: F) -> TimerResult<()> {
+        let timer_entry = self.timers.get(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let mut timer = timer_entry.write();
+        if let Some(owner_amx) = owner_amx {
+            Self::check_owner(&timer, owner_amx)?;
+        }
+        update(&mut timer)?;
+        timer.control.notify_one();
+        Ok(())
+    }
+
+    /* moves a timer into a group of its owner's timers, None removes it from its group */
+    pub fn set_group(&self, owner_amx: usize, timer_id: i32, group: Option<i32>) -> TimerResult<()> {
+        let timer_entry = self.timers.get(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let mut timer = timer_entry.write();
+        Self::check_owner(&timer, owner_amx)?;
+
+        self.indexes.set_group(timer_id, group, self.group_quota)?;
+        timer.group = group;
+
+        tracing::debug!("Timer {} group set to {:?}", timer_id, group);
+        Ok(())
+    }
+
+    /* marks a timer to be saved across restarts, see persistence */
+    pub fn set_persistent(&self, owner_amx: usize, timer_id: i32, mode: Option<PersistMode>) -> TimerResult<()> {
+        let timer_entry = self.timers.get(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let mut timer = timer_entry.write();
+        Self::check_owner(&timer, owner_amx)?;
+
+        if mode.is_some() {
+            Self::reject_cron(&timer)?;
+            if timer.player.is_some() {
+                return Err(TimerError::ParameterValidation(
+                    format!("Timer {} is bound to a player", timer_id)
+                ));
+            }
+        }
+
+        timer.persist = mode;
+        tracing::debug!("Timer {} persistence set to {:?}", timer_id, mode);
+        Ok(())
+    }
+
+    fn group_timer_ids(&self, owner_amx: usize, group: i32) -> Vec<i32> {
+        TimerIndexes::timer_ids(&self.indexes.groups, (owner_amx, group))
+    }
+
+    pub fn kill_group(&self, owner_amx: usize, group: i32) -> usize {
+        self.group_timer_ids(owner_amx, group)
+            .into_iter()
+            .filter(|&timer_id| self.kill_timer(timer_id).is_ok())
+            .count()
+    }
+
+    pub fn pause_group(&self, owner_amx: usize, group: i32) -> usize {
+        self.group_timer_ids(owner_amx, group)
+            .into_iter()
+            .filter(|&timer_id| self.pause_timer(Some(owner_amx), timer_id).is_ok())
+            .count()
+    }
+
+    pub fn resume_group(&self, owner_amx: usize, group: i32) -> usize {
+        self.group_timer_ids(owner_amx, group)
+            .into_iter()
+            .filter(|&timer_id| self.resume_timer(Some(owner_amx), timer_id).is_ok())
+            .count()
+    }
+
+    pub fn count_group(&self, owner_amx: usize, group: i32) -> usize {
+        self.group_timer_ids(owner_amx, group)
+            .into_iter()
+            .filter(|timer_id| self.timers.contains_key(timer_id))
+            .count()
+    }
+
+    /* kills the timers bound to a player, whichever script owns them */
+    pub fn kill_player_timers(&self, playerid: i32) -> usize {
+        TimerIndexes::timer_ids(&self.indexes.players, playerid)
+            .into_iter()
+            .filter(|&timer_id| self.kill_timer(timer_id).is_ok())
+            .count()
+    }
+
+    pub fn quota(&self, kind: QuotaKind) -> usize {
+        match kind {
+            QuotaKind::Script => self.script_quota,
+            QuotaKind::Group => self.group_quota,
+        }
+    }
+
+    pub fn script_timer_count(&self, owner_amx: usize) -> usize {
+        TimerIndexes::count(&self.indexes.owners, owner_amx)
+    }
+
+    pub fn kill_timers_for_amx(&self, owner_amx: usize) -> usize {
+        TimerIndexes::timer_ids(&self.indexes.owners, owner_amx)
+            .into_iter()
+            .filter(|&timer_id| self.kill_timer(timer_id).is_ok())
+            .count()
     }
 
     pub fn shutdown(&self) {
@@ -188,6 +1003,12 @@ impl TimerManager {
                         }
                     }
                     ACTIVE_TIMER_COUNT.fetch_sub(1, Ordering::Release);
+
+                    /* the index still knows the owner when the timer is locked */
+                    #[cfg(feature = "metrics")]
+                    if let Some(indexed) = self.indexes.remove_timer(timer_id) {
+                        metrics::timer_killed(indexed.owner_amx);
+                    }
                 }
             }
             /* small delay between chunks to prevent resource exhaustion */
@@ -200,6 +1021,13 @@ impl TimerManager {
             std::thread::sleep(Duration::from_millis(10));
         }
 
+        self.indexes.clear();
+
+        let dropped = callback::clear_pending_callbacks();
+        if dropped > 0 {
+            tracing::debug!("Dropped {} queued callbacks during shutdown", dropped);
+        }
+
         self.shutdown_complete.store(true, Ordering::Release);
         let final_count = ACTIVE_TIMER_COUNT.load(Ordering::Acquire);
 
@@ -210,15 +1038,134 @@ impl TimerManager {
         }
     }
 
+    /* drains expired timers; called from the server thread only */
+    pub fn process_tick(&self) -> usize {
+        callback::process_pending_callbacks(|timer_id| self.timers.contains_key(&timer_id))
+    }
+
+    pub fn dispatch_stats(&self) -> DispatchStats {
+        callback::dispatch_stats()
+    }
+
+    /* state of every timer by id, timers locked for longer than a moment are left out */
+    pub fn snapshot(&self) -> Vec<TimerSnapshot> {
+        let mut snapshots: Vec<TimerSnapshot> = self.timers
+            .iter()
+            .filter_map(|entry| entry.value().try_read_for(Duration::from_millis(10)).map(|timer| TimerSnapshot::new(&timer)))
+            .collect();
+        snapshots.sort_unstable_by_key(|snapshot| snapshot.id);
+        snapshots
+    }
+
+    /* never waits for a lock: the panicking thread may hold a shard or a timer, those are left out */
+    fn panic_snapshot(timers: &DashMap<i32, Arc<RwLock<Timer>>>) -> Vec<TimerSnapshot> {
+        let mut snapshots: Vec<TimerSnapshot> = timers
+            .shards()
+            .iter()
+            .filter_map(|shard| shard.try_read())
+            .flat_map(|shard| {
+                shard
+                    .values()
+                    .filter_map(|timer| timer.get().try_read().map(|timer| TimerSnapshot::new(&timer)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        snapshots.sort_unstable_by_key(|snapshot| snapshot.id);
+        snapshots
+    }
+
+    pub fn dump_to_file(&self, path: &Path) -> TimerResult<usize> {
+        let snapshots = self.snapshot();
+        dump::write_file(path, &snapshots)?;
+        Ok(snapshots.len())
+    }
+
+    /* dumps the timers before the panic takes the server down, until restore_panic_hook */
+    pub fn dump_on_panic(&self, path: PathBuf) {
+        let timers = Arc::clone(&self.timers);
+        let previous_hook: Arc<PanicHook> = Arc::from(std::panic::take_hook());
+        *self.previous_panic_hook.lock() = Some(Arc::clone(&previous_hook));
+
+        std::panic::set_hook(Box::new(move |info| {
+            if let Err(e) = dump::write_file(&path, &Self::panic_snapshot(&timers)) {
+                tracing::error!("Failed to dump timers after panic: {}", e);
+            }
+            previous_hook(info);
+        }));
+    }
+
+    /* puts back the hook dump_on_panic replaced, so the unloaded plugin's hook isn't left behind */
+    pub fn restore_panic_hook(&self) {
+        if let Some(previous_hook) = self.previous_panic_hook.lock().take() {
+            std::panic::set_hook(Box::new(move |info| previous_hook(info)));
+        }
+    }
+
+    /* applies `f` to every timer, keeping the Some results */
+    pub fn filter_map_timers<T, F: FnMut(&Timer) -> Option<T>>(&self, mut f: F) -> Vec<T> {
+        self.timers.iter().filter_map(|entry| f(&entry.value().read())).collect()
+    }
+
+    /* runs blocking work such as file writes off the server thread */
+    pub fn spawn_blocking<F: FnOnce() + Send + 'static>(&self, work: F) {
+        self.runtime.spawn_blocking(work);
+    }
+
+    /* ids of all active timers, in ascending order */
+    pub fn timer_ids(&self) -> Vec<i32> {
+        let mut timer_ids: Vec<i32> = self.timers.iter().map(|entry| *entry.key()).collect();
+        timer_ids.sort_unstable();
+        timer_ids
+    }
+
     pub fn active_timer_count(&self) -> usize {
         ACTIVE_TIMER_COUNT.load(Ordering::Acquire)
     }
 
-    pub fn get_timer_info(&self, timer_id: i32) -> Option<(u64, bool, String, std::time::Duration)> {
+    /* serves metrics::render on the timer runtime until the plugin unloads */
+    #[cfg(feature = "metrics")]
+    pub fn start_metrics_listener(&self, address: &str) -> TimerResult<()> {
+        let listener = std::net::TcpListener::bind(address)
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|e| TimerError::InvalidConfig(format!("Failed to listen on {}: {}", address, e)))?;
+
+        let _guard = self.runtime.enter();
+        let listener = tokio::net::TcpListener::from_std(listener)
+            .map_err(|e| TimerError::TaskSpawnError(format!("Failed to register metrics listener: {}", e)))?;
+        self.runtime.spawn(metrics::serve(listener, || ACTIVE_TIMER_COUNT.load(Ordering::Acquire)));
+
+        tracing::info!("Serving timer metrics on http://{}/metrics", address);
+        Ok(())
+    }
+
+    pub fn get_timer_stats(&self, timer_id: i32) -> TimerResult<TimerStatsSummary> {
+        let timer_entry = self.timers.get(&timer_id).ok_or(TimerError::TimerNotFound(timer_id))?;
+        let stats = timer_entry.read().stats.summary();
+        Ok(stats)
+    }
+
+    /* timers with the highest average callback duration, slowest first */
+    pub fn slowest_timers(&self, count: usize) -> Vec<(i32, TimerStatsSummary)> {
+        let mut averages: Vec<(i32, Duration)> = self.timers
+            .iter()
+            .filter_map(|entry| {
+                let timer = entry.value().read();
+                (timer.stats.successes > 0).then(|| (*entry.key(), timer.stats.average_duration()))
+            })
+            .collect();
+        averages.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        averages.truncate(count);
+
+        averages
+            .into_iter()
+            .filter_map(|(timer_id, _)| Some((timer_id, self.get_timer_stats(timer_id).ok()?)))
+            .collect()
+    }
+
+    pub fn get_timer_info(&self, timer_id: i32) -> Option<TimerInfo> {
         if let Some(timer_entry) = self.timers.get(&timer_id) {
-            let timer = timer_entry.read();
-            let elapsed = timer.created_at.elapsed();
-            Some((timer.delay_ms, timer.repeat, timer.callback.clone(), elapsed))
+            let info = timer_entry.read().info();
+            Some(info)
         } else {
             tracing::debug!("Timer {} not found in active timers (may be completed)", timer_id);
             None
@@ -229,18 +1176,19 @@ impl TimerManager {
         timer_id: i32,
         timer_arc: Arc<RwLock<Timer>>,
         timers: Arc<DashMap<i32, Arc<RwLock<Timer>>>>,
+        indexes: Arc<TimerIndexes>,
     ) {
-        let (delay_ms, repeat, callback, params) = {
+        let (owner_amx, repeat, callback, params, control) = {
             let timer_guard = timer_arc.read();
             (
-                timer_guard.delay_ms,
+                timer_guard.owner_amx,
                 timer_guard.repeat,
                 timer_guard.callback.clone(),
                 timer_guard.params.clone(),
+                Arc::clone(&timer_guard.control),
             )
         };
 
-        let delay = Duration::from_millis(delay_ms);
         let mut execution_count = 0u64;
 
         loop {
@@ -249,12 +1197,31 @@ impl TimerManager {
                 break;
             }
 
+            /* also covers timers created paused, which were never notified */
+            if timer_arc.read().is_paused() {
+                control.notified().await;
+                continue;
+            }
+
+            let deadline = timer_arc.read().wake_deadline();
+
             tokio::select! {
-                _ = sleep(delay) => {},
-                _ = tokio::task::yield_now() => {
-                    if SHUTDOWN_FLAG.load(Ordering::Acquire) {
-                        break;
+                _ = sleep_until(deadline) => {},
+                _ = control.notified() => {
+                    /* the deadline is re-read from the timer on the next iteration */
+                    while timer_arc.read().is_paused() {
+                        control.notified().await;
                     }
+                    continue;
+                }
+            }
+
+            /* a pause or interval change may have raced with the sleep */
+            {
+                let mut timer_guard = timer_arc.write();
+                timer_guard.resync();
+                if timer_guard.is_paused() || timer_guard.next_deadline > Instant::now() {
+                    continue;
                 }
             }
 
@@ -267,18 +1234,36 @@ impl TimerManager {
                 return;
             }
 
-            let callback_result = execute_callback(&callback, &params).await;
+            /* blocks this task until process_tick has run the callback on the server thread */
+            let callback_result = execute_callback(timer_id, owner_amx, &callback, &params).await;
 
             match callback_result {
-                Ok(()) => {
+                Ok(duration) => {
                     execution_count += 1;
                     tracing::trace!("Timer {} callback executed successfully (count: {})", timer_id, execution_count);
 
-                    if let Some(mut timer_guard) = timer_arc.try_write_for(Duration::from_millis(1)) {
-                        timer_guard.mark_execution();
+                    let limit_reached = {
+                        let mut timer_guard = timer_arc.write();
+                        timer_guard.mark_execution(duration);
+                        match timer_guard.executions_left.as_mut() {
+                            Some(left) => {
+                                *left = left.saturating_sub(1);
+                                *left == 0
+                            }
+                            None => false,
+                        }
+                    };
+
+                    if limit_reached {
+                        tracing::debug!("Timer {} reached its execution limit, stopping", timer_id);
+                        break;
                     }
                 }
                 Err(e) => {
+                    #[cfg(feature = "metrics")]
+                    metrics::failure(&e);
+
+                    timer_arc.write().mark_failure(&e);
                     tracing::warn!("Timer {} callback failed: {}", timer_id, e);
                     if !repeat {
                         tracing::debug!("One-shot timer {} failed, stopping", timer_id);
@@ -296,12 +1281,23 @@ impl TimerManager {
                 break;
             }
 
-            if execution_count > 1_000_000 {
+            if !timer_arc.write().advance() {
+                tracing::debug!("Timer {} has no further scheduled executions, stopping", timer_id);
+                break;
+            }
+
+            let runaway_executions = config::get().warnings.runaway_executions;
+            if runaway_executions > 0 && execution_count > runaway_executions {
                 tracing::warn!("Timer {} has executed {} times, potential runaway timer", timer_id, execution_count);
             }
         }
         if let Some((_, _)) = timers.remove(&timer_id) {
+            indexes.remove_timer(timer_id);
             ACTIVE_TIMER_COUNT.fetch_sub(1, Ordering::Release);
+
+            #[cfg(feature = "metrics")]
+            metrics::timer_completed(owner_amx);
+
             tracing::debug!("Timer {} task completed and cleaned up (executions: {})", timer_id, execution_count);
         }
     }
@@ -322,3 +1318,440 @@ impl Drop for TimerManager {
         }
     }
 }
+
+#[cfg(test)]
+impl TimerManager {
+    /* never dropped: shutdown would set the process-wide shutdown flag under the other tests */
+    pub(crate) fn for_tests() -> &'static TimerManager {
+        Self::for_tests_with_quotas(0, 0)
+    }
+
+    pub(crate) fn for_tests_with_quotas(script_quota: usize, group_quota: usize) -> &'static TimerManager {
+        let mut manager = TimerManager::new().expect("Failed to create timer manager");
+        manager.script_quota = script_quota;
+        manager.group_quota = group_quota;
+        Box::leak(Box::new(manager))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_timer(delay_ms: i32, schedule: ScheduleMode) -> Timer {
+        let mut timer = Timer::new(0, delay_ms, true, "DriftTest".to_string(), None, TimerOptions::default())
+            .expect("Failed to create timer");
+        timer.schedule = schedule;
+        timer
+    }
+
+    /* runs the timer for the given periods with a slow callback, returning the last fire time */
+    async fn run_periods(timer: &mut Timer, periods: u32, callback_time: Duration) -> Instant {
+        let mut last_fire = Instant::now();
+        for _ in 0..periods {
+            tokio::time::sleep_until(timer.next_deadline).await;
+            last_fire = Instant::now();
+            tokio::time::advance(callback_time).await;
+            timer.advance();
+        }
+        last_fire
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fixed_rate_does_not_drift() {
+        let start = Instant::now();
+        let mut timer = test_timer(1000, ScheduleMode::FixedRate(MissedTickPolicy::Burst));
+
+        let last_fire = run_periods(&mut timer, 3600, Duration::from_millis(7)).await;
+
+        assert_eq!(last_fire - start, Duration::from_secs(3600));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fixed_delay_accumulates_callback_time() {
+        let start = Instant::now();
+        let mut timer = test_timer(1000, ScheduleMode::FixedDelay);
+
+        let last_fire = run_periods(&mut timer, 3600, Duration::from_millis(7)).await;
+
+        /* every period but the first is stretched by the callback time */
+        assert_eq!(last_fire - start, Duration::from_secs(3600) + Duration::from_millis(7 * 3599));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_missed_tick_policies() {
+        let start = Instant::now();
+        let mut burst = test_timer(100, ScheduleMode::FixedRate(MissedTickPolicy::Burst));
+        let mut skip = test_timer(100, ScheduleMode::FixedRate(MissedTickPolicy::Skip));
+        let mut delay = test_timer(100, ScheduleMode::FixedRate(MissedTickPolicy::Delay));
+
+        /* first execution stalls the server for 350ms */
+        tokio::time::advance(Duration::from_millis(450)).await;
+        burst.advance();
+        skip.advance();
+        delay.advance();
+
+        assert_eq!(burst.next_deadline - start, Duration::from_millis(200));
+        assert_eq!(skip.next_deadline - start, Duration::from_millis(500));
+        assert_eq!(delay.next_deadline - start, Duration::from_millis(550));
+    }
+
+    #[test]
+    fn test_fixed_rate_fires_on_schedule() {
+        let _dispatch = callback::DISPATCH_TEST_LOCK.lock();
+        let manager = TimerManager::for_tests();
+        let period = Duration::from_millis(50);
+        let options = TimerOptions {
+            schedule: ScheduleMode::FixedRate(MissedTickPolicy::Burst),
+            ..TimerOptions::default()
+        };
+        let timer_id = manager.create_timer_with_options(0x11, 50, true, "FixedRate".to_string(), None, options)
+            .expect("Failed to create timer");
+        let timer_arc = manager.timers.get(&timer_id).map(|entry| Arc::clone(entry.value())).expect("Timer not found");
+        let start = timer_arc.read().period_start;
+
+        /* stands in for the server thread calling process_tick */
+        let mut fired = Vec::new();
+        let give_up = std::time::Instant::now() + Duration::from_secs(5);
+        while fired.len() < 5 && std::time::Instant::now() < give_up {
+            if manager.process_tick() > 0 {
+                fired.push(Instant::now());
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        manager.kill_timer(timer_id).expect("Failed to kill timer");
+
+        assert_eq!(fired.len(), 5);
+        for (period_index, fired_at) in fired.iter().enumerate() {
+            let due = start + period * (period_index as u32 + 1);
+            assert!(*fired_at >= due, "execution {} fired early", period_index);
+            assert!(*fired_at - due < Duration::from_millis(40), "execution {} fired {:?} late", period_index, *fired_at - due);
+        }
+
+        /* periods stay anchored to the first one, however late each callback ran */
+        let anchored = timer_arc.read().period_start - start;
+        assert_eq!(anchored.as_nanos() % period.as_nanos(), 0);
+        assert!(anchored >= period * 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_initial_delay_then_interval() {
+        let start = Instant::now();
+        let options = TimerOptions {
+            initial_delay_ms: Some(5000),
+            ..TimerOptions::default()
+        };
+        let mut timer = Timer::new(0, 600_000, true, "WeatherCycle".to_string(), None, options)
+            .expect("Failed to create timer");
+
+        assert_eq!(timer.next_deadline - start, Duration::from_millis(5000));
+
+        tokio::time::sleep_until(timer.next_deadline).await;
+        timer.advance();
+        assert_eq!(timer.next_deadline - start, Duration::from_millis(605_000));
+
+        let immediate = TimerOptions {
+            initial_delay_ms: Some(0),
+            ..TimerOptions::default()
+        };
+        let timer = Timer::new(0, 1000, true, "Immediate".to_string(), None, immediate)
+            .expect("Failed to create timer");
+        assert_eq!(timer.remaining(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pause_keeps_remaining_time() {
+        let mut timer = test_timer(1000, ScheduleMode::FixedRate(MissedTickPolicy::Burst));
+
+        tokio::time::advance(Duration::from_millis(300)).await;
+        assert!(timer.pause());
+        assert!(!timer.pause());
+        assert_eq!(timer.remaining(), Duration::from_millis(700));
+
+        /* time spent paused does not count towards the period */
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(timer.remaining(), Duration::from_millis(700));
+
+        assert!(timer.resume());
+        assert!(!timer.resume());
+        assert_eq!(timer.remaining(), Duration::from_millis(700));
+
+        tokio::time::advance(Duration::from_millis(700)).await;
+        assert_eq!(timer.remaining(), Duration::ZERO);
+
+        /* later periods stay aligned to the shifted schedule */
+        timer.advance();
+        assert_eq!(timer.remaining(), Duration::from_millis(1000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_set_interval_on_running_and_paused_timer() {
+        let mut timer = test_timer(1000, ScheduleMode::FixedDelay);
+
+        /* the period in progress is shortened or stretched, not restarted */
+        tokio::time::advance(Duration::from_millis(300)).await;
+        timer.set_delay(500);
+        assert_eq!(timer.remaining(), Duration::from_millis(200));
+        timer.set_delay(100);
+        assert_eq!(timer.remaining(), Duration::ZERO);
+
+        timer.pause();
+        timer.set_delay(2000);
+        assert_eq!(timer.remaining(), Duration::from_millis(1700));
+        tokio::time::advance(Duration::from_secs(10)).await;
+        timer.resume();
+        assert_eq!(timer.remaining(), Duration::from_millis(1700));
+
+        /* following periods use the new interval */
+        tokio::time::advance(Duration::from_millis(1700)).await;
+        timer.advance();
+        assert_eq!(timer.remaining(), Duration::from_millis(2000));
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        timer.rearm();
+        assert_eq!(timer.remaining(), Duration::from_millis(2000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_set_interval_during_initial_delay() {
+        let options = TimerOptions {
+            initial_delay_ms: Some(5000),
+            ..TimerOptions::default()
+        };
+        let mut timer = Timer::new(0, 1000, true, "Initial".to_string(), None, options)
+            .expect("Failed to create timer");
+
+        /* the pending initial delay is kept, the new interval starts with the next period */
+        tokio::time::advance(Duration::from_millis(300)).await;
+        timer.set_delay(2000);
+        assert_eq!(timer.remaining(), Duration::from_millis(4700));
+
+        tokio::time::advance(Duration::from_millis(4700)).await;
+        timer.advance();
+        assert_eq!(timer.remaining(), Duration::from_millis(2000));
+
+        /* after the initial delay, interval changes apply to the period in progress again */
+        tokio::time::advance(Duration::from_millis(500)).await;
+        timer.set_delay(1000);
+        assert_eq!(timer.remaining(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_set_interval_validation() {
+        let manager = TimerManager::for_tests();
+        let timer_id = manager.create_timer(0x7, 60_000, true, "Interval".to_string(), None)
+            .expect("Failed to create timer");
+
+        assert!(matches!(manager.set_interval(Some(0x7), timer_id, 0), Err(TimerError::InvalidDelay(0))));
+        assert!(manager.set_interval(Some(0x7), timer_id, 1000).is_ok());
+        assert_eq!(manager.get_timer_info(timer_id).map(|info| info.delay_ms), Some(1000));
+        assert!(matches!(manager.set_interval(Some(0x7), -5, 1000), Err(TimerError::TimerNotFound(-5))));
+        assert!(matches!(manager.set_interval(Some(0x6), timer_id, 500), Err(TimerError::NotOwner(_))));
+        assert!(matches!(manager.restart_timer(Some(0x6), timer_id), Err(TimerError::NotOwner(_))));
+        assert!(manager.restart_timer(Some(0x7), timer_id).is_ok());
+
+        let cron_id = manager.create_cron_timer(0x7, "@hourly", "Hourly".to_string(), None)
+            .expect("Failed to create cron timer");
+        assert!(matches!(manager.set_interval(Some(0x7), cron_id, 1000), Err(TimerError::ParameterValidation(_))));
+    }
+
+    #[test]
+    fn test_get_remaining() {
+        let manager = TimerManager::for_tests();
+        let timer_id = manager.create_timer(0x8, 60_000, false, "Remaining".to_string(), None)
+            .expect("Failed to create timer");
+
+        let remaining = manager.get_remaining(timer_id).expect("Timer not found");
+        assert!(remaining <= Duration::from_secs(60) && remaining > Duration::from_secs(50));
+
+        assert!(matches!(manager.pause_timer(Some(0x9), timer_id), Err(TimerError::NotOwner(_))));
+        manager.pause_timer(Some(0x8), timer_id).expect("Failed to pause timer");
+        let paused = manager.get_remaining(timer_id).expect("Timer not found");
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(manager.get_remaining(timer_id).ok(), Some(paused));
+
+        manager.kill_timer(timer_id).expect("Failed to kill timer");
+        assert!(matches!(manager.get_remaining(timer_id), Err(TimerError::TimerNotFound(_))));
+    }
+
+    #[test]
+    fn test_group_operations() {
+        let manager = TimerManager::for_tests();
+        let create = |owner_amx: usize| {
+            manager.create_timer(owner_amx, 60_000, true, "GroupTest".to_string(), None)
+                .expect("Failed to create timer")
+        };
+        let (first, second, other_group, other_script) = (create(0xA), create(0xA), create(0xA), create(0xB));
+
+        manager.set_group(0xA, first, Some(1)).expect("Failed to group timer");
+        manager.set_group(0xA, second, Some(1)).expect("Failed to group timer");
+        manager.set_group(0xA, other_group, Some(2)).expect("Failed to group timer");
+        /* the same key is a different group in another script */
+        manager.set_group(0xB, other_script, Some(1)).expect("Failed to group timer");
+        assert!(matches!(manager.set_group(0xB, first, Some(1)), Err(TimerError::NotOwner(_))));
+
+        assert_eq!(manager.count_group(0xA, 1), 2);
+        assert_eq!(manager.count_group(0xB, 1), 1);
+
+        assert_eq!(manager.pause_group(0xA, 1), 2);
+        assert!(manager.is_paused(first).unwrap() && manager.is_paused(second).unwrap());
+        assert!(!manager.is_paused(other_group).unwrap() && !manager.is_paused(other_script).unwrap());
+        assert_eq!(manager.resume_group(0xA, 1), 2);
+        assert!(!manager.is_paused(first).unwrap() && !manager.is_paused(second).unwrap());
+
+        assert_eq!(manager.kill_group(0xA, 1), 2);
+        assert_eq!(manager.count_group(0xA, 1), 0);
+        assert!(manager.get_timer_info(first).is_none() && manager.get_timer_info(second).is_none());
+        assert_eq!(manager.count_group(0xB, 1), 1);
+
+        manager.set_group(0xA, other_group, None).expect("Failed to ungroup timer");
+        assert_eq!(manager.count_group(0xA, 2), 0);
+        assert_eq!(manager.kill_group(0xA, 2), 0);
+    }
+
+    #[test]
+    fn test_script_and_group_quotas() {
+        let manager = TimerManager::for_tests_with_quotas(2, 1);
+        let create = |owner_amx: usize| manager.create_timer(owner_amx, 60_000, true, "QuotaTest".to_string(), None);
+
+        let first = create(0xC).expect("Failed to create timer");
+        let second = create(0xC).expect("Failed to create timer");
+        let over_quota = create(0xC).expect_err("Script quota not enforced");
+        assert!(matches!(over_quota, TimerError::ScriptQuotaExceeded(2)));
+        assert_eq!(over_quota.to_error_code(), -13);
+        /* every script has its own quota */
+        assert!(create(0xD).is_ok());
+
+        manager.set_group(0xC, first, Some(5)).expect("Failed to group timer");
+        let full_group = manager.set_group(0xC, second, Some(5)).expect_err("Group quota not enforced");
+        assert!(matches!(full_group, TimerError::GroupQuotaExceeded(1)));
+        assert_eq!(full_group.to_error_code(), -14);
+        assert_eq!(manager.count_group(0xC, 5), 1);
+
+        /* killing a timer frees its place in the script and in its group */
+        manager.kill_timer(first).expect("Failed to kill timer");
+        assert_eq!(manager.script_timer_count(0xC), 1);
+        manager.set_group(0xC, second, Some(5)).expect("Failed to group timer");
+        assert!(create(0xC).is_ok());
+        assert!(create(0xC).is_err());
+    }
+
+    #[test]
+    fn test_script_quota_under_concurrent_creates() {
+        let manager = TimerManager::for_tests_with_quotas(20, 0);
+
+        let created: usize = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| {
+                    (0..10)
+                        .filter(|_| manager.create_timer(0xE, 60_000, true, "QuotaRace".to_string(), None).is_ok())
+                        .count()
+                }))
+                .collect();
+            workers.into_iter().map(|worker| worker.join().expect("Worker panicked")).sum()
+        });
+
+        assert_eq!(created, 20);
+        assert_eq!(manager.script_timer_count(0xE), 20);
+    }
+
+    #[test]
+    fn test_kill_locked_timer() {
+        let manager = TimerManager::for_tests();
+        let timer_id = manager.create_timer(0xF, 60_000, true, "Locked".to_string(), None)
+            .expect("Failed to create timer");
+        manager.set_group(0xF, timer_id, Some(1)).expect("Failed to group timer");
+        let timer_arc = manager.timers.get(&timer_id).map(|entry| Arc::clone(entry.value())).expect("Timer not found");
+
+        let held = timer_arc.write();
+        manager.kill_timer(timer_id).expect("Failed to kill timer");
+        assert_eq!(manager.script_timer_count(0xF), 0);
+        assert_eq!(manager.count_group(0xF, 1), 0);
+        assert_eq!(manager.kill_timers_for_amx(0xF), 0);
+        drop(held);
+
+        /* the task is aborted once the lock is released */
+        let give_up = std::time::Instant::now() + Duration::from_secs(5);
+        let finished = || timer_arc.read().task_handle.as_ref().is_some_and(|handle| handle.is_finished());
+        while !finished() && std::time::Instant::now() < give_up {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(finished());
+    }
+
+    #[test]
+    fn test_cron_timer_schedule() {
+        let timezone = crate::cron::CronTimezone::Fixed(0);
+        let never = TimerOptions {
+            schedule: ScheduleMode::Cron(CronSchedule::parse("0 0 31 2 *", timezone).unwrap()),
+            ..TimerOptions::default()
+        };
+        assert!(Timer::new(0, CRON_RESOLUTION_MS, true, "Never".to_string(), None, never).is_err());
+
+        let hourly = TimerOptions {
+            schedule: ScheduleMode::Cron(CronSchedule::parse("@hourly", timezone).unwrap()),
+            ..TimerOptions::default()
+        };
+        let timer = Timer::new(0, CRON_RESOLUTION_MS, true, "Hourly".to_string(), None, hourly)
+            .expect("Failed to create timer");
+        let wall_deadline = timer.wall_deadline.expect("Cron timer without a deadline");
+        assert_eq!(wall_deadline.timestamp() % 3600, 0);
+        assert!(timer.remaining() <= Duration::from_secs(3600));
+        assert_eq!(timer.info().delay_ms, timer.remaining().as_millis() as u64);
+    }
+
+    #[test]
+    fn test_cron_timezone_per_script() {
+        let manager = TimerManager::for_tests();
+        manager.set_cron_timezone(0x40, CronTimezone::Fixed(420));
+
+        let create = |owner_amx| manager.create_cron_timer(owner_amx, "@daily", "Daily".to_string(), None)
+            .expect("Failed to create cron timer");
+        let timezone = |timer_id| match manager.timers.get(&timer_id).expect("Timer not found").read().schedule {
+            ScheduleMode::Cron(schedule) => schedule.timezone,
+            _ => panic!("Not a cron timer"),
+        };
+
+        assert_eq!(timezone(create(0x40)), CronTimezone::Fixed(420));
+        assert_eq!(timezone(create(0x41)), config::get().cron.timezone());
+
+        manager.clear_cron_timezone(0x40);
+        assert_eq!(timezone(create(0x40)), config::get().cron.timezone());
+    }
+
+    #[test]
+    fn test_delay_until_timestamp() {
+        let now = DateTime::from_timestamp_millis(1_700_000_000_250).unwrap();
+
+        assert_eq!(delay_until_timestamp(1_700_000_060, now, false).unwrap(), 59_750);
+        /* the current second is not in the past yet */
+        assert_eq!(delay_until_timestamp(1_700_000_000, now, false).unwrap(), 0);
+        assert!(matches!(
+            delay_until_timestamp(1_699_999_999, now, false),
+            Err(TimerError::TimestampInPast(1_699_999_999))
+        ));
+        assert_eq!(delay_until_timestamp(1_699_999_999, now, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_timer_stats() {
+        let mut stats = TimerStats::default();
+        for ms in 1..=200 {
+            stats.record_success(Duration::from_millis(ms));
+        }
+        stats.record_failure(&TimerError::CallbackExecutionError("amx_Exec failed".to_string()));
+
+        let summary = stats.summary();
+        assert_eq!(summary.successes, 200);
+        assert_eq!(summary.failures, 1);
+        assert_eq!(summary.min_duration, Duration::from_millis(1));
+        assert_eq!(summary.max_duration, Duration::from_millis(200));
+        assert_eq!(summary.avg_duration, Duration::from_micros(100_500));
+        assert_eq!(summary.p99_duration, Duration::from_millis(198));
+        assert!(summary.last_error.is_some_and(|error| error.contains("amx_Exec failed")));
+
+        let empty = TimerStats::default().summary();
+        assert_eq!(empty.p99_duration, Duration::ZERO);
+        assert_eq!(empty.avg_duration, Duration::ZERO);
+    }
+}